@@ -0,0 +1,1830 @@
+use crate::physics::{self, AnalysisResult, LoadCaseResults, SolverMethod, Stability};
+use crate::snap::{self, Snapped, Target};
+use crate::table::{Sheet, Sort, Table};
+use crate::units::{self, ForceEntry, Unit, UnitSystem};
+use crate::view::View;
+use crate::{
+    Combination, ConnectionData, Edit, EditorSettings, Entity, Force, History, LoadCase, Material,
+    Member, Model, Part, Point, Project, Section, Settlement, TrussError,
+};
+use egui::{Key, Pos2};
+
+#[derive(Debug, Default)]
+pub enum MessageType {
+    #[default]
+    Forcemsg,
+    Command,
+}
+/// The editor: a model plus the state of drawing and commanding it.
+#[derive(Default, Debug)]
+pub struct Truss {
+    pub model: Model,
+    pub settings: EditorSettings,
+    pub last_node: Option<usize>,
+    pub mode: Mode,
+    pub messagetyp: MessageType,
+    pub input_buf: String,
+    pub result: Option<AnalysisResult>,
+    /// Last error or status line shown in the command bar.
+    pub message: Option<String>,
+    /// Mechanism mode animated on the canvas, two entries per node.
+    pub mechanism: Option<Vec<f64>>,
+    pub view: View,
+    /// Canvas area of the last frame. `None` until the first frame, when the
+    /// view is placed as the workspace block describes.
+    pub canvas: Option<egui::Rect>,
+    /// Entities selected in Edit mode.
+    pub selection: Vec<Entity>,
+    pub drag: Option<Drag>,
+    /// Mode to go back to when the command bar closes.
+    pub resume: Mode,
+    pub history: History,
+    /// Axial force drawn at full colour in Solve mode, in N. `None` scales
+    /// to the largest member force.
+    pub axial_scale: Option<f64>,
+    /// Hide member force and reaction labels in Solve mode.
+    pub hide_labels: bool,
+    /// Factor displacements are multiplied by in the deformed shape. `None`
+    /// makes the largest `DEFORMED_SIZE` of the structure's size.
+    pub magnification: Option<f64>,
+    pub hide_deformed: bool,
+    /// Swing between the undeformed and deformed shapes.
+    pub animate_deformed: bool,
+    /// Show the results table beside the canvas.
+    pub show_table: bool,
+    pub sheet: Sheet,
+    pub sort: Sort,
+    /// Every load case and combination, when the model has more than one.
+    pub load_results: Option<LoadCaseResults>,
+    /// Case or combination of `load_results` shown as `result`.
+    pub showing: Option<String>,
+    /// Classification of `model`, or `None` if the model has changed since.
+    pub stability: Option<Result<Stability, TrussError>>,
+}
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Command,
+    #[default]
+    Insert,
+    TextEdit,
+    Edit,
+    Solve,
+}
+/// A primary-button drag in Edit mode.
+#[derive(Debug, Clone)]
+pub enum Drag {
+    /// Moving the selected nodes, from where they were when it started.
+    Move { from: Vec<(usize, Point)> },
+    /// Selecting everything inside the box from `start` to the pointer.
+    Box { start: Pos2 },
+}
+#[derive(Debug)]
+pub enum Connection {
+    Pin,
+    Roller,
+    Joint,
+}
+// UI
+/// Peak on-screen displacement of an animated mechanism, in pixels.
+const MECHANISM_AMPLITUDE: f64 = 20.0;
+/// Seconds per swing of an animated mechanism.
+const MECHANISM_PERIOD: f64 = 1.5;
+/// Scrolled pixels that zoom by a factor of e.
+const ZOOM_PER_SCROLL: f32 = 200.0;
+/// Distance, in pixels, at which the cursor snaps to nodes, midpoints and
+/// alignments.
+const SNAP_RADIUS: f32 = 10.0;
+/// Grid lines closer than this, in pixels, are thinned out.
+const MIN_GRID_SPACING: f32 = 8.0;
+/// Distance, in pixels, within which a click picks a member or force.
+const PICK_RADIUS: f32 = 5.0;
+/// Member forces below this fraction of the colour scale are drawn as zero.
+const ZERO_FORCE: f64 = 1e-3;
+/// Factor the `+` and `-` keys change the colour scale by in Solve mode.
+const SCALE_STEP: f64 = 1.25;
+const TENSION: egui::Color32 = egui::Color32::from_rgb(70, 130, 255);
+const COMPRESSION: egui::Color32 = egui::Color32::from_rgb(255, 70, 70);
+const ZERO: egui::Color32 = egui::Color32::GRAY;
+/// Largest displacement of the automatically magnified deformed shape, as a
+/// fraction of the larger side of the structure.
+const DEFORMED_SIZE: f64 = 0.1;
+/// Seconds from undeformed to deformed and back when animated.
+const DEFORMED_PERIOD: f64 = 2.0;
+/// Length, in pixels, of the arrow drawn for the largest applied load.
+/// Other loads and reactions are drawn to the same scale.
+const FORCE_ARROW: f32 = 60.0;
+/// Factor the `[` and `]` keys change the magnification by.
+const MAGNIFY_STEP: f64 = 2.0;
+
+impl eframe::App for Truss {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.handle_mode(ctx);
+        if self.show_table {
+            self.draw_table_panel(ctx);
+        }
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            let canvas = self.handle_camera(ui);
+            if let Mode::Edit = self.mode {
+                self.handle_edit_pointer(ui, &canvas);
+            }
+            self.draw_scene(ui, ctx);
+        });
+        // Reclassify once a drag ends rather than on every frame of it.
+        if !matches!(self.drag, Some(Drag::Move { .. })) {
+            self.stability();
+        }
+        self.draw_command_bar(ctx);
+    }
+}
+
+impl Truss {
+    fn handle_mode(&mut self, ctx: &egui::Context) {
+        if matches!(self.mode, Mode::Insert | Mode::Command | Mode::Edit) {
+            self.handle_history_keys(ctx);
+        }
+        match self.mode {
+            Mode::Insert => self.handle_insert(ctx),
+            Mode::Command => self.handle_command(ctx),
+            Mode::TextEdit => self.handle_text_edit(ctx),
+            Mode::Edit => self.handle_edit(ctx),
+            Mode::Solve => self.handle_solve(ctx),
+        }
+    }
+
+    fn handle_insert(&mut self, ctx: &egui::Context) {
+        if ctx.input(|i| i.key_pressed(egui::Key::Space))
+            && let Some(pos) = ctx.input(|i| i.pointer.hover_pos())
+        {
+            let result = self.handle_insert_click(pos, Connection::Joint);
+            self.report(result);
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::P))
+            && let Some(pos) = ctx.input(|i| i.pointer.hover_pos())
+        {
+            let result = self.handle_insert_click(pos, Connection::Pin);
+            self.report(result);
+        }
+
+        if ctx.input(|i| i.key_pressed(egui::Key::R) && !i.modifiers.ctrl)
+            && let Some(pos) = ctx.input(|i| i.pointer.hover_pos())
+        {
+            let result = self.handle_insert_click(pos, Connection::Roller);
+            self.report(result);
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::F)) {
+            self.mode = Mode::TextEdit;
+            self.resume = Mode::Insert;
+        }
+
+        if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            self.mode = Mode::Command;
+        }
+    }
+
+    /// `u` undoes and Ctrl-r redoes, as in vim.
+    fn handle_history_keys(&mut self, ctx: &egui::Context) {
+        if ctx.input(|i| i.key_pressed(Key::U) && i.modifiers.is_none()) {
+            self.undo();
+        }
+        if ctx.input(|i| i.key_pressed(Key::R) && i.modifiers.ctrl) {
+            self.redo();
+        }
+    }
+
+    fn new_member(&self, p1: usize, p2: usize) -> Member {
+        Member {
+            p1,
+            p2,
+            material: self.settings.active_material,
+            section: self.settings.active_section,
+        }
+    }
+
+    /// Applies `edit` to the model and records it for undo.
+    fn apply(&mut self, edit: Edit) {
+        self.history.apply(&mut self.model, edit);
+        self.clear_results();
+    }
+
+    /// Sets `parts` of the model as one edit, leaving out those that are
+    /// already so.
+    fn set(&mut self, parts: impl IntoIterator<Item = Part>) {
+        let edits: Vec<Edit> = (parts.into_iter())
+            .filter_map(|part| Edit::set(&self.model, part))
+            .collect();
+        match edits.len() {
+            0 => {}
+            1 => self.apply(edits.into_iter().next().unwrap()),
+            _ => self.apply(Edit::Group(edits)),
+        }
+    }
+
+    /// Records a move in progress and drops any other drag, before an edit
+    /// that renumbers nodes or rewinds the history.
+    fn interrupt_drag(&mut self) {
+        if matches!(self.drag, Some(Drag::Move { .. })) {
+            self.end_drag(None, false);
+        }
+        self.drag = None;
+    }
+
+    fn undo(&mut self) {
+        self.interrupt_drag();
+        if self.history.undo(&mut self.model) {
+            self.after_history();
+        } else {
+            self.message = Some("nothing to undo".to_string());
+        }
+    }
+
+    fn redo(&mut self) {
+        self.interrupt_drag();
+        if self.history.redo(&mut self.model) {
+            self.after_history();
+        } else {
+            self.message = Some("nothing to redo".to_string());
+        }
+    }
+
+    /// Drops results that no longer match the model.
+    fn clear_results(&mut self) {
+        self.result = None;
+        self.load_results = None;
+        self.mechanism = None;
+        self.stability = None;
+    }
+
+    /// Classifies the model, reusing the last classification until the
+    /// model changes.
+    fn stability(&mut self) -> &Result<Stability, TrussError> {
+        self.stability
+            .get_or_insert_with(|| physics::classify(&self.model))
+    }
+
+    /// Drops editor state that may refer to what an undo or redo removed.
+    fn after_history(&mut self) {
+        self.selection.clear();
+        self.drag = None;
+        self.last_node = None;
+        self.clear_results();
+        let library = &self.model.library;
+        self.settings.active_material = self
+            .settings
+            .active_material
+            .min(library.materials.len().saturating_sub(1));
+        self.settings.active_section = self
+            .settings
+            .active_section
+            .min(library.sections.len().saturating_sub(1));
+        self.settings.active_case = (self.settings.active_case).min(self.model.case_count() - 1);
+    }
+
+    fn report(&mut self, result: Result<(), TrussError>) {
+        if let Err(err) = result {
+            self.message = Some(err.to_string());
+        }
+    }
+
+    fn handle_insert_click(
+        &mut self,
+        pos: egui::Pos2,
+        jointtype: Connection,
+    ) -> Result<(), TrussError> {
+        let snapped = self.snap(pos);
+        let hit = match snapped.target {
+            Target::Node(idx) => Some(idx),
+            _ => self.hit_test(pos),
+        };
+        match jointtype {
+            Connection::Joint => {
+                if let Some(idx) = hit {
+                    let p1 = self.last_node.unwrap_or(self.model.points.len() - 1);
+                    if p1 == idx {
+                        return Err(TrussError::ZeroLengthMember(self.model.edges.len()));
+                    }
+                    self.apply(Edit::AddMember(self.new_member(p1, idx)));
+                    self.last_node = Some(idx);
+                } else if self.model.points.is_empty() {
+                    self.apply(Edit::AddNode(snapped.point));
+                } else {
+                    let p1 = self.last_node.unwrap_or(self.model.points.len() - 1);
+                    let member = self.new_member(p1, self.model.points.len());
+                    let mut edits = vec![Edit::AddNode(snapped.point)];
+                    // A node on a midpoint joins the member it lies on, which
+                    // already connects it to that member's ends.
+                    let mut joined = false;
+                    if let Target::Midpoint(split) = snapped.target {
+                        edits.push(Edit::SplitMember(split));
+                        let split = &self.model.edges[split];
+                        joined = p1 == split.p1 || p1 == split.p2;
+                    }
+                    if !joined {
+                        edits.push(Edit::AddMember(member));
+                    }
+                    self.apply(Edit::Group(edits));
+                    self.last_node = None;
+                }
+            }
+            Connection::Roller => {
+                if let Some(idx) = hit {
+                    self.apply(Edit::AddSupport(ConnectionData::Roller(
+                        idx,
+                        self.settings.roller_angle,
+                    )));
+                    self.last_node = Some(idx);
+                } else {
+                    return Err(TrussError::SupportOffNode);
+                }
+            }
+            Connection::Pin => {
+                if let Some(idx) = hit {
+                    self.apply(Edit::AddSupport(ConnectionData::Pin(idx)));
+                    self.last_node = Some(idx);
+                } else {
+                    return Err(TrussError::SupportOffNode);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_command(&mut self, ctx: &egui::Context) {
+        if ctx.input(|i| i.key_pressed(egui::Key::I)) {
+            self.mode = Mode::Insert;
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::Z)) {
+            self.fit_view();
+        }
+        if ctx.input(|i| i.key_pressed(egui::Key::E)) {
+            self.mode = Mode::Edit;
+        }
+
+        if ctx.input(|i| i.key_pressed(egui::Key::Space))
+            && let Some(pos) = ctx.input(|i| i.pointer.hover_pos())
+            && let Some(idx) = self.hit_test(pos)
+        {
+            self.last_node = Some(idx);
+        }
+        if ctx.input(|i| i.key_pressed(Key::Colon)) {
+            self.mode = Mode::TextEdit;
+            self.messagetyp = MessageType::Command;
+            self.resume = Mode::Insert;
+        }
+    }
+
+    /// Keys of the results view: `+` and `-` strengthen and weaken the
+    /// colours by changing their scale, `L` toggles labels, `D` the
+    /// deformed shape and `A` its animation, `]` and `[` magnify it more
+    /// and less, and `T` shows the results table.
+    fn handle_solve(&mut self, ctx: &egui::Context) {
+        let Some(result) = &self.result else {
+            self.mode = Mode::Command;
+            return;
+        };
+        let scale = self.axial_scale.unwrap_or_else(|| largest_force(result));
+        if ctx.input(|i| i.key_pressed(Key::Plus) || i.key_pressed(Key::Equals)) {
+            self.axial_scale = Some(scale / SCALE_STEP);
+        }
+        if ctx.input(|i| i.key_pressed(Key::Minus)) {
+            self.axial_scale = Some(scale * SCALE_STEP);
+        }
+        if ctx.input(|i| i.key_pressed(Key::L)) {
+            self.hide_labels = !self.hide_labels;
+        }
+        let magnification = self.magnification(result);
+        if ctx.input(|i| i.key_pressed(Key::CloseBracket)) {
+            self.magnification = Some(magnification * MAGNIFY_STEP);
+        }
+        if ctx.input(|i| i.key_pressed(Key::OpenBracket)) {
+            self.magnification = Some(magnification / MAGNIFY_STEP);
+        }
+        if ctx.input(|i| i.key_pressed(Key::D)) {
+            self.hide_deformed = !self.hide_deformed;
+        }
+        if ctx.input(|i| i.key_pressed(Key::A)) {
+            self.animate_deformed = !self.animate_deformed;
+        }
+        if ctx.input(|i| i.key_pressed(Key::T)) {
+            self.show_table = !self.show_table;
+        }
+        if ctx.input(|i| i.key_pressed(Key::I)) {
+            self.mode = Mode::Insert;
+        }
+        if ctx.input(|i| i.key_pressed(Key::E)) {
+            self.mode = Mode::Edit;
+        }
+        if ctx.input(|i| i.key_pressed(Key::Colon)) {
+            self.mode = Mode::TextEdit;
+            self.messagetyp = MessageType::Command;
+            self.resume = Mode::Solve;
+        }
+        if ctx.input(|i| i.key_pressed(Key::Escape)) {
+            self.mode = Mode::Command;
+        }
+    }
+
+    /// Keys of Edit mode. Selecting and dragging are done with the pointer
+    /// in `handle_edit_pointer`.
+    fn handle_edit(&mut self, ctx: &egui::Context) {
+        if ctx.input(|i| i.key_pressed(Key::Delete) || i.key_pressed(Key::X)) {
+            self.delete_selection();
+        }
+        if ctx.input(|i| i.key_pressed(Key::I)) {
+            self.mode = Mode::Insert;
+        }
+        if ctx.input(|i| i.key_pressed(Key::Colon)) {
+            self.mode = Mode::TextEdit;
+            self.messagetyp = MessageType::Command;
+            self.resume = Mode::Edit;
+        }
+        if ctx.input(|i| i.key_pressed(Key::Escape)) {
+            if self.selection.is_empty() {
+                self.mode = Mode::Command;
+            } else {
+                self.selection.clear();
+            }
+        }
+    }
+
+    /// Click selects what is under the pointer, clicking again cycles
+    /// through everything there, and Shift adds to the selection. Dragging
+    /// a node moves the selected nodes; dragging elsewhere selects a box.
+    fn handle_edit_pointer(&mut self, ui: &egui::Ui, canvas: &egui::Response) {
+        let (origin, shift) = ui.input(|i| (i.pointer.press_origin(), i.modifiers.shift));
+        if canvas.clicked_by(egui::PointerButton::Primary)
+            && let Some(pos) = canvas.interact_pointer_pos()
+        {
+            self.select_at(pos, shift);
+        }
+        if canvas.drag_started_by(egui::PointerButton::Primary)
+            && let Some(origin) = origin
+        {
+            self.drag = match self.pick(origin).first() {
+                Some(&Entity::Node(node)) => {
+                    if !self.selection.contains(&Entity::Node(node)) {
+                        self.selection = vec![Entity::Node(node)];
+                    }
+                    let from = (self.selection.iter())
+                        .filter_map(|entity| match *entity {
+                            Entity::Node(node) => Some((node, self.model.points[node])),
+                            _ => None,
+                        })
+                        .collect();
+                    Some(Drag::Move { from })
+                }
+                _ => Some(Drag::Box { start: origin }),
+            };
+        }
+        // Nodes follow the pointer directly and the whole drag is recorded
+        // as one move when it ends.
+        if canvas.dragged_by(egui::PointerButton::Primary) {
+            self.drag_nodes(canvas.drag_delta());
+        }
+        if canvas.drag_stopped() {
+            self.end_drag(canvas.interact_pointer_pos(), shift);
+        }
+    }
+
+    /// Moves the nodes of a move drag by `delta` screen pixels.
+    fn drag_nodes(&mut self, delta: egui::Vec2) {
+        let Some(Drag::Move { from }) = &self.drag else {
+            return;
+        };
+        let delta = delta / self.view.scale;
+        if delta != egui::Vec2::ZERO {
+            for (node, _) in from {
+                let point = self.model.points[*node];
+                (self.model).move_node(*node, Point::new(point.x + delta.x, point.y - delta.y));
+            }
+            self.clear_results();
+        }
+    }
+
+    /// Selects what a box drag covers, or records a move drag, with the
+    /// pointer released at `end`.
+    fn end_drag(&mut self, end: Option<Pos2>, shift: bool) {
+        match self.drag.take() {
+            Some(Drag::Box { start }) => {
+                if let Some(end) = end {
+                    self.select_box(egui::Rect::from_two_pos(start, end), shift);
+                }
+            }
+            Some(Drag::Move { from }) => {
+                let moves: Vec<_> = (from.into_iter())
+                    .map(|(node, from)| (node, from, self.model.points[node]))
+                    .filter(|(_, from, to)| from != to)
+                    .collect();
+                if !moves.is_empty() {
+                    self.history.record(Edit::MoveNodes(moves));
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// Everything drawn under screen position `pos`: the node first, then
+    /// its supports, forces and finally members.
+    fn pick(&self, pos: Pos2) -> Vec<Entity> {
+        let mut picked = Vec::new();
+        if let Some(node) = self.hit_test(pos) {
+            picked.push(Entity::Node(node));
+            for (idx, connection) in self.model.connections.iter().enumerate() {
+                if connection.node() == node {
+                    picked.push(Entity::Support(idx));
+                }
+            }
+        }
+        for (idx, force) in self.model.force.iter().enumerate() {
+            if segment_distance(pos, self.screen(force.p1), self.force_tip(force)) < PICK_RADIUS {
+                picked.push(Entity::Force(idx));
+            }
+        }
+        for (idx, member) in self.model.edges.iter().enumerate() {
+            if segment_distance(pos, self.screen(member.p1), self.screen(member.p2)) < PICK_RADIUS {
+                picked.push(Entity::Member(idx));
+            }
+        }
+        picked
+    }
+
+    fn select_at(&mut self, pos: Pos2, add: bool) {
+        let picked = self.pick(pos);
+        let next = match self.selection.as_slice() {
+            [only] if !add => (picked.iter())
+                .position(|entity| entity == only)
+                .map(|idx| picked[(idx + 1) % picked.len()]),
+            _ => None,
+        }
+        .or(picked.first().copied());
+        if !add {
+            self.selection.clear();
+        }
+        if let Some(entity) = next {
+            match self.selection.iter().position(|e| *e == entity) {
+                Some(idx) => {
+                    self.selection.remove(idx);
+                }
+                None => self.selection.push(entity),
+            }
+        }
+    }
+
+    /// Selects the nodes inside `rect` with their supports and forces, and
+    /// the members with both ends inside.
+    fn select_box(&mut self, rect: egui::Rect, add: bool) {
+        if !add {
+            self.selection.clear();
+        }
+        let inside = |node: usize| rect.contains(self.screen(node));
+        let mut boxed: Vec<Entity> = (0..self.model.points.len())
+            .filter(|&node| inside(node))
+            .map(Entity::Node)
+            .collect();
+        boxed.extend(
+            (self.model.edges.iter().enumerate())
+                .filter(|(_, m)| inside(m.p1) && inside(m.p2))
+                .map(|(idx, _)| Entity::Member(idx)),
+        );
+        boxed.extend(
+            (self.model.connections.iter().enumerate())
+                .filter(|(_, c)| inside(c.node()))
+                .map(|(idx, _)| Entity::Support(idx)),
+        );
+        boxed.extend(
+            (self.model.force.iter().enumerate())
+                .filter(|(_, f)| inside(f.p1))
+                .map(|(idx, _)| Entity::Force(idx)),
+        );
+        for entity in boxed {
+            if !self.selection.contains(&entity) {
+                self.selection.push(entity);
+            }
+        }
+    }
+
+    fn delete_selection(&mut self) {
+        if self.selection.is_empty() {
+            return;
+        }
+        self.interrupt_drag();
+        let selection = std::mem::take(&mut self.selection);
+        let removed = self.model.remove(&selection);
+        self.history.record(Edit::Remove(selection, removed));
+        self.clear_results();
+        self.last_node = None;
+    }
+
+    /// Moves the one selected node to `x`, `y`, given in length units.
+    fn move_selected_node(&mut self, x: &str, y: &str) {
+        let [Entity::Node(node)] = self.selection[..] else {
+            self.message = Some("select a single node first".to_string());
+            return;
+        };
+        let length = self.settings.units.length;
+        match (units::parse_value(x, length), units::parse_value(y, length)) {
+            (Ok(x), Ok(y)) => {
+                let from = self.model.points[node];
+                self.apply(Edit::MoveNodes(vec![(
+                    node,
+                    from,
+                    Point::new(x as f32, y as f32),
+                )]));
+            }
+            _ => self.message = Some("usage: node <x> <y>".to_string()),
+        }
+    }
+
+    fn handle_text_edit(&mut self, ctx: &egui::Context) {
+        use egui::*;
+        match &self.messagetyp {
+            MessageType::Forcemsg => {
+                TopBottomPanel::bottom("nvim_command_bar")
+                    .exact_height(28.0)
+                    .show(ctx, |ui| {
+                        Frame::new().show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    RichText::new(":").color(Color32::LIGHT_GREEN).monospace(),
+                                );
+
+                                let text_edit = TextEdit::singleline(&mut self.input_buf)
+                                    .font(TextStyle::Monospace)
+                                    .desired_width(f32::INFINITY)
+                                    .frame(false);
+
+                                let response = ui.add(text_edit);
+
+                                // Auto-focus when entering mode
+                                if ui.memory(|m| !m.has_focus(response.id)) {
+                                    response.request_focus();
+                                }
+
+                                // Enter submits
+                                if ui.input(|i| i.key_pressed(Key::Enter)) {
+                                    self.submit_force(ctx);
+                                    response.highlight();
+                                }
+
+                                // Esc cancels
+                                if ui.input(|i| i.key_pressed(Key::Escape)) {
+                                    self.input_buf.clear();
+                                    self.mode = self.resume;
+                                }
+                            });
+                        });
+                    });
+            }
+            MessageType::Command => {
+                TopBottomPanel::bottom("nvim_command_bar")
+                    .exact_height(28.0)
+                    .show(ctx, |ui| {
+                        Frame::new().show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    RichText::new(":").color(Color32::LIGHT_GREEN).monospace(),
+                                );
+
+                                let text_edit = TextEdit::singleline(&mut self.input_buf)
+                                    .font(TextStyle::Monospace)
+                                    .desired_width(f32::INFINITY)
+                                    .frame(false);
+
+                                let response = ui.add(text_edit);
+
+                                if ui.memory(|m| !m.has_focus(response.id)) {
+                                    response.request_focus();
+                                }
+
+                                // Enter submits
+                                if ui.input(|i| i.key_pressed(Key::Enter)) {
+                                    self.submit_command();
+                                    response.highlight();
+                                }
+
+                                // Esc cancels
+                                if ui.input(|i| i.key_pressed(Key::Escape)) {
+                                    self.input_buf.clear();
+                                    self.mode = self.resume;
+                                }
+                            });
+                        });
+                    });
+            }
+        }
+    }
+
+    /// Adds the force typed as `fx,fy`, `mag@angle` or a magnitude toward
+    /// the mouse, at the last node drawn.
+    fn submit_force(&mut self, ctx: &egui::Context) {
+        match units::parse_force(&self.input_buf, self.settings.units.force) {
+            Ok(ForceEntry::Components(fx, fy)) => {
+                let result = self.add_force(fx, fy);
+                self.report(result);
+            }
+            Ok(ForceEntry::Toward(mag)) => {
+                if let Some(pos) = ctx.input(|i| i.pointer.hover_pos()) {
+                    // Without a node `add_force` reports the error, whatever
+                    // the direction.
+                    let (dx, dy) = self.force_node().map_or((1.0, 0.0), |node| {
+                        let (from, to) = (self.model.points[node], self.view.to_world(pos));
+                        ((to.x - from.x) as f64, (to.y - from.y) as f64)
+                    });
+                    let length = dx.hypot(dy);
+                    let result = self.add_force(mag * dx / length, mag * dy / length);
+                    self.report(result);
+                }
+            }
+            Err(err) => self.message = Some(err),
+        }
+
+        self.input_buf.clear();
+        self.mode = self.resume;
+    }
+
+    /// Node new forces go on: the last one drawn, or else the newest.
+    fn force_node(&self) -> Option<usize> {
+        self.last_node.or(self.model.points.len().checked_sub(1))
+    }
+
+    /// Adds a force of `fx`, `fy` N at the node from `force_node`.
+    fn add_force(&mut self, fx: f64, fy: f64) -> Result<(), TrussError> {
+        let index = self.model.force.len();
+        let Some(p1) = self.force_node() else {
+            return Err(TrussError::DanglingIndex {
+                kind: "force",
+                index,
+                node: 0,
+            });
+        };
+        let mag = fx.hypot(fy);
+        if mag == 0.0 || !mag.is_finite() {
+            return Err(TrussError::ZeroLengthForce(index));
+        }
+        // `p2` is one model unit from the node, along the force.
+        let start = self.model.points[p1];
+        let p2 = Point::new(start.x + (fx / mag) as f32, start.y + (fy / mag) as f32);
+        let mag = mag as f32;
+        let case = self.settings.active_case;
+        self.apply(Edit::AddForce(Force { p1, p2, mag, case }));
+        Ok(())
+    }
+
+    fn submit_command(&mut self) {
+        let input = std::mem::take(&mut self.input_buf);
+        let args: Vec<&str> = input.split_whitespace().collect();
+        self.message = None;
+        match args.as_slice() {
+            ["e", path] => match Project::load(path) {
+                Ok(project) => {
+                    self.open(project);
+                    self.message = Some(format!("opened {path}"));
+                }
+                Err(err) => self.message = Some(err.to_string()),
+            },
+            ["solve"] => {
+                self.solve();
+                if self.result.is_some() {
+                    self.resume = Mode::Solve;
+                }
+            }
+            ["show", name] => {
+                self.show(Some(name.to_string()));
+                if self.result.is_some() {
+                    self.resume = Mode::Solve;
+                }
+            }
+            ["undo"] => self.undo(),
+            ["redo"] => self.redo(),
+            ["node", x, y] => self.move_selected_node(x, y),
+            ["delete"] => self.delete_selection(),
+            _ => self.run_command(&args),
+        }
+
+        self.mode = self.resume;
+    }
+
+    fn run_command(&mut self, args: &[&str]) {
+        match args {
+            ["w", path] => match Project::new(self.model.clone(), self.settings.clone()).save(path)
+            {
+                Ok(()) => self.message = Some(format!("wrote {path}")),
+                Err(err) => self.message = Some(err.to_string()),
+            },
+            ["solver", "elimination"] => self.set([Part::Solver(SolverMethod::Elimination)]),
+            ["solver", "penalty"] => self.set([Part::Solver(SolverMethod::Penalty)]),
+            ["settle", node, dx, dy] => match (
+                node.parse(),
+                units::parse_value(dx, self.settings.units.length),
+                units::parse_value(dy, self.settings.units.length),
+            ) {
+                (Ok(node), Ok(dx), Ok(dy)) => {
+                    let (dx, dy) = (dx as f32, dy as f32);
+                    let mut settlements = self.model.settlements.clone();
+                    settlements.retain(|s| s.node != node);
+                    settlements.push(Settlement { node, dx, dy });
+                    self.set([Part::Settlements(settlements)]);
+                }
+                _ => self.message = Some("usage: settle <node> <dx> <dy>".to_string()),
+            },
+            ["material", name, youngs, rest @ ..] => {
+                let units = self.settings.units;
+                let yield_stress = rest
+                    .first()
+                    .map_or(Ok(f64::INFINITY), |v| units::parse_value(v, units.stress));
+                match (
+                    units::parse_value(youngs, units.modulus),
+                    yield_stress,
+                    rest.len() <= 1,
+                ) {
+                    (Ok(youngs), Ok(yield_stress), true) => {
+                        let mut library = self.model.library.clone();
+                        self.settings.active_material = library.set_material(Material {
+                            name: name.to_string(),
+                            youngs,
+                            yield_stress,
+                        });
+                        self.set([Part::Library(library)]);
+                    }
+                    _ => {
+                        self.message = Some("usage: material <name> <E> [yield stress]".to_string())
+                    }
+                }
+            }
+            ["section", name, area] => match self.settings.units.parse_area(area) {
+                Ok(area) => {
+                    let mut library = self.model.library.clone();
+                    self.settings.active_section = library.set_section(Section {
+                        name: name.to_string(),
+                        area,
+                    });
+                    self.set([Part::Library(library)]);
+                }
+                Err(_) => self.message = Some("usage: section <name> <area>".to_string()),
+            },
+            ["mechanism"] => self.show_mechanism(0),
+            ["mechanism", "off"] => self.mechanism = None,
+            ["mechanism", idx] => match idx.parse() {
+                Ok(idx) => self.show_mechanism(idx),
+                Err(_) => self.message = Some("usage: mechanism [index|off]".to_string()),
+            },
+            ["roller", angle] => match angle.parse::<f32>() {
+                Ok(angle) if angle.is_finite() => self.settings.roller_angle = angle,
+                _ => self.message = Some("usage: roller [node] <degrees>".to_string()),
+            },
+            ["roller", node, angle] => match (node.parse::<usize>(), angle.parse::<f32>()) {
+                (Ok(node), Ok(angle)) if angle.is_finite() => {
+                    let rollers: Vec<Part> = (self.model.connections.iter().enumerate())
+                        .filter(|(_, c)| matches!(c, ConnectionData::Roller(n, _) if *n == node))
+                        .map(|(idx, _)| Part::Support(idx, ConnectionData::Roller(node, angle)))
+                        .collect();
+                    if rollers.is_empty() {
+                        self.message = Some(format!("no roller at node {node}"));
+                    }
+                    self.set(rollers);
+                }
+                _ => self.message = Some("usage: roller [node] <degrees>".to_string()),
+            },
+            ["use", name] => {
+                if let Some(idx) = self.model.library.material_index(name) {
+                    self.settings.active_material = idx;
+                } else if let Some(idx) = self.model.library.section_index(name) {
+                    self.settings.active_section = idx;
+                } else {
+                    self.message = Some(format!("no material or section named {name}"));
+                }
+            }
+            ["assign", member, names @ ..] => self.assign(member, names),
+            ["fit"] => self.fit_view(),
+            ["case"] => {
+                let names: Vec<String> = (0..self.model.case_count())
+                    .map(|case| match case == self.settings.active_case {
+                        true => format!("[{}]", self.model.case_name(case)),
+                        false => self.model.case_name(case).to_string(),
+                    })
+                    .collect();
+                self.message = Some(format!("load cases: {}", names.join(" ")));
+            }
+            ["case", name] => {
+                self.settings.active_case = match self.model.case_index(name) {
+                    Some(case) => case,
+                    None => {
+                        self.apply(Edit::AddCase(LoadCase {
+                            name: name.to_string(),
+                        }));
+                        self.model.load_cases.len() - 1
+                    }
+                }
+            }
+            ["combo", name, terms @ ..] if !terms.is_empty() && terms.len() % 2 == 0 => {
+                let factors: Result<Vec<(usize, f64)>, String> = terms
+                    .chunks(2)
+                    .map(|term| {
+                        let factor =
+                            (term[0].parse()).map_err(|_| format!("not a factor: {}", term[0]))?;
+                        let case = (self.model.case_index(term[1]))
+                            .ok_or_else(|| format!("no load case named {}", term[1]))?;
+                        Ok((case, factor))
+                    })
+                    .collect();
+                match factors {
+                    Ok(factors) => {
+                        let combination = Combination {
+                            name: name.to_string(),
+                            factors,
+                        };
+                        match (self.model.combinations.iter()).position(|c| c.name == *name) {
+                            Some(idx) => self.set([Part::Combination(idx, combination)]),
+                            None => self.apply(Edit::AddCombination(combination)),
+                        }
+                    }
+                    Err(err) => self.message = Some(err),
+                }
+            }
+            ["combo", ..] => {
+                self.message =
+                    Some("usage: combo <name> <factor> <case> [<factor> <case>]…".to_string())
+            }
+            ["scale", "auto"] => self.axial_scale = None,
+            ["scale", force] => match units::parse_value(force, self.settings.units.force) {
+                Ok(force) if force > 0.0 => self.axial_scale = Some(force),
+                _ => self.message = Some("usage: scale auto|<force>".to_string()),
+            },
+            ["labels", "on"] => self.hide_labels = false,
+            ["labels", "off"] => self.hide_labels = true,
+            ["magnify", "auto"] => self.magnification = None,
+            ["magnify", factor] => match factor.parse() {
+                Ok(factor) if factor > 0.0 => self.magnification = Some(factor),
+                _ => self.message = Some("usage: magnify auto|<factor>".to_string()),
+            },
+            ["table", "on"] => self.show_table = true,
+            ["table", "off"] => self.show_table = false,
+            ["deformed", "on"] => self.hide_deformed = false,
+            ["deformed", "off"] => self.hide_deformed = true,
+            ["deformed", "animate"] => {
+                self.hide_deformed = false;
+                self.animate_deformed = !self.animate_deformed;
+            }
+            ["grid", "on"] => self.settings.snap.show_grid = true,
+            ["grid", "off"] => self.settings.snap.show_grid = false,
+            ["grid", spacing @ ..] if matches!(spacing.len(), 1 | 2) => {
+                let length = self.settings.units.length;
+                match (
+                    units::parse_value(spacing[0], length),
+                    units::parse_value(spacing.last().unwrap(), length),
+                ) {
+                    (Ok(dx), Ok(dy)) if dx > 0.0 && dy > 0.0 => {
+                        let mut workspace = self.model.workspace.clone();
+                        workspace.grid_x = dx as f32;
+                        workspace.grid_y = dy as f32;
+                        self.set([Part::Workspace(workspace)]);
+                    }
+                    _ => self.message = Some("usage: grid on|off|<dx> [dy]".to_string()),
+                }
+            }
+            ["snap"] => {
+                let snap = self.settings.snap;
+                self.message = Some(format!(
+                    "snap: grid {}, nodes {}, midpoints {}, align {}",
+                    snap.grid, snap.nodes, snap.midpoints, snap.align
+                ));
+            }
+            ["snap", state @ ("on" | "off")] => {
+                let on = *state == "on";
+                for kind in ["grid", "nodes", "midpoints", "align"] {
+                    *self.settings.snap.toggle(kind).unwrap() = on;
+                }
+            }
+            ["snap", kind, rest @ ..] if rest.len() <= 1 => {
+                match (self.settings.snap.toggle(kind), rest.first()) {
+                    (Some(switch), None) => *switch = !*switch,
+                    (Some(switch), Some(&"on")) => *switch = true,
+                    (Some(switch), Some(&"off")) => *switch = false,
+                    _ => {
+                        self.message = Some(
+                            "usage: snap [on|off|grid|nodes|midpoints|align [on|off]]".to_string(),
+                        )
+                    }
+                }
+            }
+            ["units"] => self.message = Some(self.settings.units.to_string()),
+            ["units", preset] => match UnitSystem::preset(preset) {
+                Some(units) => self.settings.units = units,
+                None => self.message = Some("usage: units si|us|<kind> <unit>".to_string()),
+            },
+            ["units", kind, symbol] => match Unit::from_symbol(symbol) {
+                Some(unit) => {
+                    if let Err(err) = self.settings.units.set(kind, unit) {
+                        self.message = Some(err);
+                    }
+                }
+                None => self.message = Some(format!("unknown unit {symbol}")),
+            },
+            [] => {}
+            _ => self.message = Some(format!("not a command: {}", args.join(" "))),
+        }
+    }
+
+    /// Replaces the model and editor settings with those of `project` and
+    /// zooms to show it.
+    fn open(&mut self, project: Project) {
+        *self = Truss {
+            model: project.model,
+            settings: project.editor,
+            canvas: self.canvas,
+            ..Default::default()
+        };
+        self.fit_view();
+    }
+
+    /// Middle-drag pans and the scroll wheel zooms about the cursor. Returns
+    /// the canvas response for the other pointer handling.
+    fn handle_camera(&mut self, ui: &mut egui::Ui) -> egui::Response {
+        let rect = ui.max_rect();
+        if self.canvas.is_none() {
+            self.view = View::from_workspace(&self.model.workspace, rect);
+        }
+        self.canvas = Some(rect);
+
+        let response = ui.interact(rect, ui.id().with("canvas"), egui::Sense::click_and_drag());
+        if response.dragged_by(egui::PointerButton::Middle) {
+            self.view.pan(response.drag_delta());
+        }
+        if let Some(pos) = response.hover_pos() {
+            let (scroll, zoom) = ui.input(|i| (i.smooth_scroll_delta.y, i.zoom_delta()));
+            let factor = zoom * (scroll / ZOOM_PER_SCROLL).exp();
+            if factor != 1.0 {
+                self.view.zoom_about(pos, factor);
+            }
+        }
+        response
+    }
+
+    /// Zooms so that the whole structure fills the canvas.
+    fn fit_view(&mut self) {
+        if let Some(rect) = self.canvas
+            && let Some(view) = self.view.fit(&self.model.points, rect)
+        {
+            self.view = view;
+        }
+    }
+
+    /// Screen position of `node`.
+    fn screen(&self, node: usize) -> Pos2 {
+        self.view.to_screen(self.model.points[node])
+    }
+
+    /// Where a node placed at screen position `pos` lands.
+    fn snap(&self, pos: Pos2) -> Snapped {
+        snap::snap(
+            &self.model,
+            &self.settings.snap,
+            self.view.to_world(pos),
+            SNAP_RADIUS / self.view.scale,
+        )
+    }
+
+    /// The node drawn under screen position `pos`, if any.
+    fn hit_test(&self, pos: Pos2) -> Option<usize> {
+        (0..self.model.points.len()).find(|&node| self.screen(node).distance(pos) < 8.0)
+    }
+
+    /// Gives member `member` every material and section named in `names`.
+    fn assign(&mut self, member: &str, names: &[&str]) {
+        let Some(idx) = member
+            .parse::<usize>()
+            .ok()
+            .filter(|idx| *idx < self.model.edges.len())
+        else {
+            self.message = Some(format!("no member {member}"));
+            return;
+        };
+        let mut member = self.model.edges[idx].clone();
+        for name in names {
+            if let Some(material) = self.model.library.material_index(name) {
+                member.material = material;
+            } else if let Some(section) = self.model.library.section_index(name) {
+                member.section = section;
+            } else {
+                self.message = Some(format!("no material or section named {name}"));
+            }
+        }
+        self.set([Part::Member(idx, member)]);
+    }
+
+    /// Shows mechanism mode `idx` of the current model, if it has one.
+    fn show_mechanism(&mut self, idx: usize) {
+        self.mechanism = None;
+        match self.stability().clone() {
+            Ok(stability) => match stability.modes.into_iter().nth(idx) {
+                Some(mode) => self.mechanism = Some(mode),
+                None => self.message = Some(format!("no mechanism {idx}")),
+            },
+            Err(err) => self.message = Some(err.to_string()),
+        }
+    }
+
+    fn solve(&mut self) {
+        self.clear_results();
+        if self.model.load_cases.len() > 1 || !self.model.combinations.is_empty() {
+            match physics::solve_load_cases(&self.model) {
+                Ok(results) => {
+                    self.load_results = Some(results);
+                    self.show(self.showing.clone());
+                }
+                Err(err) => self.solve_failed(err),
+            }
+            return;
+        }
+        match physics::solve_stiff(&self.model) {
+            Ok(result) => {
+                let eq = &result.diagnostics.equilibrium;
+                let units = self.settings.units;
+                self.message = Some(format!(
+                    "solved: ΣFx = {:.3e} {force}, ΣFy = {:.3e} {force}, ΣM = {:.3e} {force}·{length}",
+                    units.force.from_si(eq.fx),
+                    units.force.from_si(eq.fy),
+                    units.force.from_si(units.length.from_si(eq.moment)),
+                    force = units.force,
+                    length = units.length,
+                ));
+                self.result = Some(result);
+            }
+            Err(err) => self.solve_failed(err),
+        }
+    }
+
+    fn solve_failed(&mut self, err: TrussError) {
+        if let TrussError::Unstable(_) = err {
+            self.show_mechanism(0);
+        }
+        self.message = Some(err.to_string());
+    }
+
+    /// Shows load case or combination `name` of the last solve, or the first
+    /// combination (the first case without any) if `name` is `None` or gone.
+    fn show(&mut self, name: Option<String>) {
+        let Some(results) = &self.load_results else {
+            self.message = Some("solve first".to_string());
+            return;
+        };
+        let found = (name.as_deref())
+            .and_then(|name| results.named(name).map(|result| (name.to_string(), result)))
+            .or_else(|| {
+                (results.combinations.first())
+                    .or(results.cases.first())
+                    .map(|named| (named.name.clone(), &named.result))
+            });
+        match found {
+            Some((name, result)) => {
+                self.result = Some(result.clone());
+                self.message = Some(format!("showing {name}"));
+                self.showing = Some(name);
+            }
+            None => self.message = Some("nothing to show".to_string()),
+        }
+    }
+
+    fn draw_scene(&self, ui: &mut egui::Ui, ctx: &egui::Context) {
+        let painter = ui.painter();
+
+        if self.settings.snap.show_grid {
+            self.draw_grid(painter, ui.max_rect());
+        }
+
+        // Preview line, ending where the next node would snap to
+        if let Mode::Insert = self.mode
+            && let Some(pos) = ctx.input(|i| i.pointer.hover_pos())
+        {
+            let snapped = self.snap(pos);
+            let end = self.view.to_screen(snapped.point);
+            if !self.model.points.is_empty() {
+                painter.line_segment(
+                    [
+                        self.screen(self.last_node.unwrap_or(self.model.points.len() - 1)),
+                        end,
+                    ],
+                    egui::Stroke::new(1.0, egui::Color32::WHITE),
+                );
+            }
+            self.draw_snap(painter, &snapped);
+        }
+
+        // Draw points
+        for node in 0..self.model.points.len() {
+            painter.circle_stroke(
+                self.screen(node),
+                3.0,
+                egui::Stroke::new(1.0, egui::Color32::WHITE),
+            );
+        }
+        for connection in &self.model.connections {
+            match connection {
+                ConnectionData::Roller(idx, angle) => {
+                    let center = self.screen(*idx);
+                    painter.circle(
+                        center,
+                        6.0,
+                        egui::Color32::GREEN,
+                        egui::Stroke::new(1.0, egui::Color32::GREEN),
+                    );
+                    // Rolling surface, perpendicular to the restrained direction.
+                    let (sin, cos) = angle.to_radians().sin_cos();
+                    let normal = self.view.vector_to_screen(cos, sin).normalized();
+                    let tangent = normal.rot90();
+                    let surface = center + normal * 8.0;
+                    painter.line_segment(
+                        [surface - tangent * 10.0, surface + tangent * 10.0],
+                        egui::Stroke::new(2.0, egui::Color32::GREEN),
+                    );
+                }
+                ConnectionData::Pin(idx) => {
+                    painter.circle(
+                        self.screen(*idx),
+                        6.0,
+                        egui::Color32::ORANGE,
+                        egui::Stroke::new(1.0, egui::Color32::ORANGE),
+                    );
+                }
+            }
+        }
+
+        // Draw members, coloured by axial force in the results view
+        let results = match (self.mode, &self.result) {
+            (Mode::Solve, Some(result)) => Some(result),
+            _ => None,
+        };
+        let scale = results.map(|result| self.axial_scale.unwrap_or_else(|| largest_force(result)));
+        for (idx, member) in self.model.edges.iter().enumerate() {
+            let colour = match (results.and_then(|r| r.member(idx)), scale) {
+                (Some(result), Some(scale)) => axial_colour(result.force, scale),
+                _ => egui::Color32::RED,
+            };
+            painter.line_segment(
+                [self.screen(member.p1), self.screen(member.p2)],
+                egui::Stroke::new(2.0, colour),
+            );
+        }
+        // Forces act from `p1` toward `p2`, which is how they are applied,
+        // and are drawn to scale with the largest. Forces of other load
+        // cases are dimmed.
+        for force in &self.model.force {
+            let colour = match force.case == self.settings.active_case {
+                true => egui::Color32::GREEN,
+                false => egui::Color32::DARK_GREEN,
+            };
+            draw_arrow(
+                painter,
+                self.screen(force.p1),
+                self.force_tip(force),
+                egui::Stroke::new(3.0, colour),
+            );
+        }
+
+        if let (Some(result), Some(scale)) = (results, scale) {
+            if !self.hide_deformed {
+                self.draw_deformed(painter, ctx, result);
+            }
+            self.draw_results(painter, ui.max_rect(), result, scale);
+        }
+
+        self.draw_selection(painter);
+        if let Some(Drag::Box { start }) = self.drag
+            && let Some(pos) = ctx.input(|i| i.pointer.hover_pos())
+        {
+            painter.rect_stroke(
+                egui::Rect::from_two_pos(start, pos),
+                0.0,
+                egui::Stroke::new(1.0, egui::Color32::LIGHT_BLUE),
+                egui::StrokeKind::Inside,
+            );
+        }
+
+        if let Some(mode) = &self.mechanism {
+            self.draw_mechanism(painter, ctx, mode);
+        }
+    }
+
+    /// Pixels per newton of drawn force arrows, which makes the largest
+    /// applied load `FORCE_ARROW` long whatever the units or zoom.
+    fn arrow_scale(&self) -> f32 {
+        let largest = (self.model.force.iter()).fold(0.0, |max: f32, f| max.max(f.mag));
+        if largest > 0.0 {
+            FORCE_ARROW / largest
+        } else {
+            0.0
+        }
+    }
+
+    /// Screen position of the tip of the arrow drawn for `force`.
+    fn force_tip(&self, force: &Force) -> Pos2 {
+        let start = self.model.points[force.p1];
+        let direction = self
+            .view
+            .vector_to_screen(force.p2.x - start.x, force.p2.y - start.y);
+        self.screen(force.p1) + direction.normalized() * force.mag * self.arrow_scale()
+    }
+
+    /// Force and stress labels on members, reaction arrows at supports and
+    /// a legend of the colour scale in the top right of `rect`.
+    fn draw_results(
+        &self,
+        painter: &egui::Painter,
+        rect: egui::Rect,
+        result: &AnalysisResult,
+        scale: f64,
+    ) {
+        let units = self.settings.units;
+        let arrow_scale = self.arrow_scale();
+        let font = egui::FontId::proportional(12.0);
+        if !self.hide_labels {
+            for member in &result.members {
+                let (a, b) = (self.screen(member.p1), self.screen(member.p2));
+                painter.text(
+                    a + (b - a) / 2.0,
+                    egui::Align2::CENTER_BOTTOM,
+                    format!(
+                        "{:.3} {} / {:.3} {}",
+                        units.force.from_si(member.force),
+                        units.force,
+                        units.stress.from_si(member.stress),
+                        units.stress,
+                    ),
+                    font.clone(),
+                    egui::Color32::WHITE,
+                );
+            }
+        }
+
+        // Reactions push on the structure, so the arrows end at the node.
+        let stroke = egui::Stroke::new(2.0, egui::Color32::from_rgb(255, 170, 0));
+        for reaction in &result.reactions {
+            let tip = self.screen(reaction.node);
+            let tail = tip - egui::vec2(reaction.rx as f32, -reaction.ry as f32) * arrow_scale;
+            draw_arrow(painter, tail, tip, stroke);
+            if !self.hide_labels {
+                painter.text(
+                    tail,
+                    egui::Align2::CENTER_CENTER,
+                    format!(
+                        "({:.3}, {:.3}) {}",
+                        units.force.from_si(reaction.rx),
+                        units.force.from_si(reaction.ry),
+                        units.force
+                    ),
+                    font.clone(),
+                    stroke.color,
+                );
+            }
+        }
+
+        let bar = egui::Rect::from_min_size(
+            rect.right_top() + egui::vec2(-220.0, 16.0),
+            egui::vec2(200.0, 12.0),
+        );
+        let steps = 40;
+        for step in 0..steps {
+            let t = (step as f64 + 0.5) / steps as f64;
+            let left = bar.left() + bar.width() * step as f32 / steps as f32;
+            painter.rect_filled(
+                egui::Rect::from_x_y_ranges(
+                    left..=left + bar.width() / steps as f32,
+                    bar.y_range(),
+                ),
+                0.0,
+                axial_colour((2.0 * t - 1.0) * scale, scale),
+            );
+        }
+        let scale = units.force.from_si(scale);
+        for (anchor, align, text) in [
+            (
+                bar.left_bottom(),
+                egui::Align2::LEFT_TOP,
+                format!("C {scale:.3}"),
+            ),
+            (
+                bar.center_bottom(),
+                egui::Align2::CENTER_TOP,
+                "0".to_string(),
+            ),
+            (
+                bar.right_bottom(),
+                egui::Align2::RIGHT_TOP,
+                format!("T {scale:.3} {}", units.force),
+            ),
+        ] {
+            painter.text(
+                anchor + egui::vec2(0.0, 2.0),
+                align,
+                text,
+                font.clone(),
+                ZERO,
+            );
+        }
+    }
+
+    fn magnification(&self, result: &AnalysisResult) -> f64 {
+        self.magnification.unwrap_or_else(|| {
+            let largest = result.diagnostics.max_displacement;
+            let (mut min, mut max) = (
+                Point::new(f32::MAX, f32::MAX),
+                Point::new(f32::MIN, f32::MIN),
+            );
+            for p in &self.model.points {
+                min = Point::new(min.x.min(p.x), min.y.min(p.y));
+                max = Point::new(max.x.max(p.x), max.y.max(p.y));
+            }
+            let size = f64::from((max.x - min.x).max(max.y - min.y));
+            if largest > 0.0 && size > 0.0 {
+                DEFORMED_SIZE * size / largest
+            } else {
+                1.0
+            }
+        })
+    }
+
+    /// The structure with its displacements magnified, and the node that
+    /// moves furthest marked.
+    fn draw_deformed(&self, painter: &egui::Painter, ctx: &egui::Context, result: &AnalysisResult) {
+        let mut magnification = self.magnification(result);
+        if self.animate_deformed {
+            let phase = ctx.input(|i| i.time) * std::f64::consts::TAU / DEFORMED_PERIOD;
+            magnification *= (1.0 - phase.cos()) / 2.0;
+            ctx.request_repaint();
+        }
+        let mut moved = self.model.points.clone();
+        for d in &result.displacements {
+            moved[d.node].x += (d.dx * magnification) as f32;
+            moved[d.node].y += (d.dy * magnification) as f32;
+        }
+        let moved: Vec<Pos2> = moved.into_iter().map(|p| self.view.to_screen(p)).collect();
+
+        let stroke = egui::Stroke::new(1.5, egui::Color32::from_rgb(120, 220, 220));
+        for member in &self.model.edges {
+            painter.line_segment([moved[member.p1], moved[member.p2]], stroke);
+        }
+        for pos in &moved {
+            painter.circle_filled(*pos, 2.0, stroke.color);
+        }
+        if let Some(largest) = result.largest_displacement() {
+            let length = self.settings.units.length;
+            painter.circle_stroke(moved[largest.node], 7.0, stroke);
+            painter.text(
+                moved[largest.node] + egui::vec2(10.0, 0.0),
+                egui::Align2::LEFT_CENTER,
+                format!(
+                    "max {:.3e} {length} at node {} (×{:.1})",
+                    length.from_si(result.diagnostics.max_displacement),
+                    largest.node,
+                    self.magnification(result),
+                ),
+                egui::FontId::proportional(12.0),
+                stroke.color,
+            );
+        }
+    }
+
+    fn draw_selection(&self, painter: &egui::Painter) {
+        let stroke = egui::Stroke::new(2.0, egui::Color32::GOLD);
+        for entity in &self.selection {
+            match *entity {
+                Entity::Node(node) => {
+                    painter.circle_stroke(self.screen(node), 5.0, stroke);
+                }
+                Entity::Member(idx) => {
+                    let member = &self.model.edges[idx];
+                    painter.line_segment(
+                        [self.screen(member.p1), self.screen(member.p2)],
+                        egui::Stroke::new(4.0, egui::Color32::GOLD),
+                    );
+                }
+                Entity::Support(idx) => {
+                    painter.circle_stroke(
+                        self.screen(self.model.connections[idx].node()),
+                        9.0,
+                        stroke,
+                    );
+                }
+                Entity::Force(idx) => {
+                    let force = &self.model.force[idx];
+                    painter.line_segment(
+                        [self.screen(force.p1), self.force_tip(force)],
+                        egui::Stroke::new(5.0, egui::Color32::GOLD),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Grid lines every `grid-x` by `grid-y` model units, thinned out when
+    /// they would crowd together, with the axes drawn brighter.
+    fn draw_grid(&self, painter: &egui::Painter, rect: egui::Rect) {
+        let workspace = &self.model.workspace;
+        let (top_left, bottom_right) = (
+            self.view.to_world(rect.left_top()),
+            self.view.to_world(rect.right_bottom()),
+        );
+        let faint = egui::Stroke::new(1.0, egui::Color32::from_gray(40));
+        let axis = egui::Stroke::new(1.0, egui::Color32::from_gray(90));
+        let lines = |spacing: f32, from: f32, to: f32| {
+            let every = (MIN_GRID_SPACING / (spacing * self.view.scale))
+                .ceil()
+                .max(1.0);
+            let step = spacing * every;
+            let (first, last) = ((from / step).floor() as i64, (to / step).ceil() as i64);
+            (first..=last).map(move |k| (k == 0, k as f32 * step))
+        };
+        if workspace.grid_x > 0.0 {
+            for (is_axis, x) in lines(workspace.grid_x, top_left.x, bottom_right.x) {
+                let x = self.view.to_screen(Point::new(x, 0.0)).x;
+                painter.vline(x, rect.y_range(), if is_axis { axis } else { faint });
+            }
+        }
+        if workspace.grid_y > 0.0 {
+            for (is_axis, y) in lines(workspace.grid_y, bottom_right.y, top_left.y) {
+                let y = self.view.to_screen(Point::new(0.0, y)).y;
+                painter.hline(rect.x_range(), y, if is_axis { axis } else { faint });
+            }
+        }
+    }
+
+    /// Marks what the cursor snapped to, with guides to the nodes it lines
+    /// up with.
+    fn draw_snap(&self, painter: &egui::Painter, snapped: &Snapped) {
+        let at = self.view.to_screen(snapped.point);
+        let stroke = egui::Stroke::new(1.0, egui::Color32::LIGHT_BLUE);
+        match snapped.target {
+            Target::Node(_) => {
+                painter.circle_stroke(at, 7.0, stroke);
+            }
+            Target::Midpoint(_) => {
+                let r = 6.0;
+                painter.add(egui::Shape::closed_line(
+                    vec![
+                        at + egui::vec2(0.0, -r),
+                        at + egui::vec2(r, r * 0.7),
+                        at + egui::vec2(-r, r * 0.7),
+                    ],
+                    stroke,
+                ));
+            }
+            Target::Grid => {
+                painter.line_segment(
+                    [at - egui::vec2(4.0, 0.0), at + egui::vec2(4.0, 0.0)],
+                    stroke,
+                );
+                painter.line_segment(
+                    [at - egui::vec2(0.0, 4.0), at + egui::vec2(0.0, 4.0)],
+                    stroke,
+                );
+            }
+            Target::Free => {}
+        }
+        let guide = egui::Stroke::new(1.0, egui::Color32::from_rgb(90, 120, 160));
+        for node in [snapped.vertical, snapped.horizontal].into_iter().flatten() {
+            painter.extend(egui::Shape::dashed_line(
+                &[self.screen(node), at],
+                guide,
+                4.0,
+                4.0,
+            ));
+        }
+    }
+
+    /// Swings the structure back and forth along a mechanism mode.
+    fn draw_mechanism(&self, painter: &egui::Painter, ctx: &egui::Context, mode: &[f64]) {
+        if mode.len() != 2 * self.model.points.len() {
+            return;
+        }
+        let time = ctx.input(|i| i.time);
+        let swing = (time * std::f64::consts::TAU / MECHANISM_PERIOD).sin() * MECHANISM_AMPLITUDE;
+        let moved = |node: usize| {
+            // Modes are in world axes, y up.
+            self.screen(node)
+                + egui::vec2(
+                    (mode[2 * node] * swing) as f32,
+                    -(mode[2 * node + 1] * swing) as f32,
+                )
+        };
+        let stroke = egui::Stroke::new(2.0, egui::Color32::YELLOW);
+        for member in &self.model.edges {
+            painter.line_segment([moved(member.p1), moved(member.p2)], stroke);
+        }
+        for node in 0..self.model.points.len() {
+            if mode[2 * node].hypot(mode[2 * node + 1]) > 1e-6 {
+                painter.circle_stroke(moved(node), 5.0, stroke);
+            }
+        }
+        ctx.request_repaint();
+    }
+
+    /// Member or node results, sorted by clicking a header. Clicking a row
+    /// selects its member or node on the canvas.
+    fn draw_table_panel(&mut self, ctx: &egui::Context) {
+        let Some(result) = &self.result else {
+            return;
+        };
+        let units = self.settings.units;
+        let mut table = match (self.sheet, &self.load_results) {
+            (Sheet::Envelope, Some(results)) => Table::envelope(results, units),
+            (Sheet::Nodes, _) => Table::nodes(&self.model, result, units),
+            _ => Table::members(&self.model, result, units),
+        };
+        table.sort(self.sort);
+        let (mut sheet, mut sort, mut clicked) = (self.sheet, self.sort, None);
+
+        egui::SidePanel::right("results table")
+            .resizable(true)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut sheet, Sheet::Members, "Members");
+                    ui.selectable_value(&mut sheet, Sheet::Nodes, "Nodes");
+                    if self.load_results.is_some() {
+                        ui.selectable_value(&mut sheet, Sheet::Envelope, "Envelope");
+                    }
+                    if ui.button("Copy TSV").clicked() {
+                        ui.ctx().copy_text(table.to_tsv());
+                    }
+                });
+                ui.separator();
+                egui::ScrollArea::both().show(ui, |ui| {
+                    egui::Grid::new("results grid")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (column, (header, _)) in table.columns.iter().enumerate() {
+                                let arrow = match sort {
+                                    Sort { column: c, .. } if c != column => "",
+                                    Sort {
+                                        descending: true, ..
+                                    } => " ▼",
+                                    Sort { .. } => " ▲",
+                                };
+                                if ui.button(format!("{header}{arrow}")).clicked() {
+                                    sort = Sort {
+                                        column,
+                                        descending: sort.column == column && !sort.descending,
+                                    };
+                                }
+                            }
+                            ui.end_row();
+                            for (idx, row) in table.rows.iter().enumerate() {
+                                let selected = self.selection.contains(&row.entity);
+                                if ui.selectable_label(selected, table.cell(idx, 0)).clicked() {
+                                    clicked = Some(row.entity);
+                                }
+                                for column in 1..table.columns.len() {
+                                    ui.label(table.cell(idx, column));
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+
+        if sheet != self.sheet {
+            self.sheet = sheet;
+            self.sort = Sort::default();
+        } else {
+            self.sort = sort;
+        }
+        if let Some(entity) = clicked {
+            self.selection = vec![entity];
+        }
+    }
+
+    fn draw_command_bar(&self, ctx: &egui::Context) {
+        egui::TopBottomPanel::bottom("command bar").show(ctx, |ui| {
+            let mode_str = match self.mode {
+                Mode::Edit => "Edit",
+                Mode::TextEdit => "Input",
+                Mode::Command => "Command",
+                Mode::Solve => "Solve",
+                Mode::Insert => "Insert",
+            };
+
+            ui.horizontal(|ui| {
+                ui.label(mode_str);
+                if let [Entity::Node(node)] = self.selection[..] {
+                    let (length, point) = (self.settings.units.length, self.model.points[node]);
+                    ui.separator();
+                    ui.label(format!(
+                        "node {node} at ({:.4}, {:.4}) {length}",
+                        length.from_si(point.x.into()),
+                        length.from_si(point.y.into()),
+                    ));
+                } else if !self.selection.is_empty() {
+                    ui.separator();
+                    ui.label(format!("{} selected", self.selection.len()));
+                }
+                if !self.model.points.is_empty() {
+                    ui.separator();
+                    match &self.stability {
+                        Some(Ok(stability)) if stability.is_stable() => {
+                            ui.label(stability.to_string());
+                        }
+                        Some(Ok(stability)) => {
+                            ui.colored_label(egui::Color32::YELLOW, stability.to_string());
+                        }
+                        Some(Err(err)) => {
+                            ui.colored_label(egui::Color32::YELLOW, err.to_string());
+                        }
+                        None => {}
+                    }
+                }
+                if let Some(message) = &self.message {
+                    ui.separator();
+                    ui.colored_label(egui::Color32::LIGHT_RED, message);
+                }
+            });
+        });
+    }
+
+    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+        // Customize egui here with cc.egui_ctx.set_fonts and cc.egui_ctx.set_visuals.
+        // Restore app state using cc.storage (requires the "persistence" feature).
+        // Use the cc.gl (a glow::Context) to create graphics shaders and buffers that you can use
+        // for e.g. egui::PaintCallback.
+        Self::default()
+    }
+}
+
+/// Distance from `p` to the segment from `a` to `b`.
+fn segment_distance(p: Pos2, a: Pos2, b: Pos2) -> f32 {
+    let ab = b - a;
+    let t = if ab.length_sq() > 0.0 {
+        ((p - a).dot(ab) / ab.length_sq()).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    p.distance(a + ab * t)
+}
+
+/// Largest member force in `result`, or 1 N if nothing carries load.
+fn largest_force(result: &AnalysisResult) -> f64 {
+    let largest = (result.members.iter())
+        .map(|m| m.force.abs())
+        .fold(0.0, f64::max);
+    if largest > 0.0 { largest } else { 1.0 }
+}
+
+/// Tension blue and compression red, fading to grey as the force drops
+/// from `scale` to zero.
+fn axial_colour(force: f64, scale: f64) -> egui::Color32 {
+    let t = (force.abs() / scale).min(1.0);
+    if t < ZERO_FORCE {
+        return ZERO;
+    }
+    let full = if force > 0.0 { TENSION } else { COMPRESSION };
+    ZERO.lerp_to_gamma(full, t as f32)
+}
+
+/// A line from `tail` to `tip` with a head at `tip`.
+fn draw_arrow(painter: &egui::Painter, tail: Pos2, tip: Pos2, stroke: egui::Stroke) {
+    painter.line_segment([tail, tip], stroke);
+    let dir = tip - tail;
+    if dir.length() == 0.0 {
+        return;
+    }
+    let dir = dir.normalized() * 10.0;
+    for side in [dir.rot90(), -dir.rot90()] {
+        painter.line_segment([tip, tip - dir + side * 0.5], stroke);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deleting_during_a_drag_ends_it() {
+        let mut truss = Truss::default();
+        truss.model.points = vec![Point::new(0.0, 0.0), Point::new(1.0, 0.0)];
+        truss.selection = vec![Entity::Node(1)];
+        truss.drag = Some(Drag::Move {
+            from: vec![(1, truss.model.points[1])],
+        });
+        truss.drag_nodes(egui::vec2(truss.view.scale, 0.0));
+
+        truss.delete_selection();
+        truss.drag_nodes(egui::vec2(truss.view.scale, 0.0));
+        truss.end_drag(None, false);
+        assert_eq!(truss.model.points, [Point::new(0.0, 0.0)]);
+
+        // The move is undone separately from the delete.
+        truss.undo();
+        assert_eq!(truss.model.points[1], Point::new(2.0, 0.0));
+        truss.undo();
+        assert_eq!(truss.model.points[1], Point::new(1.0, 0.0));
+
+        // Undoing during a drag records the move first and then undoes it.
+        truss.drag = Some(Drag::Move {
+            from: vec![(1, truss.model.points[1])],
+        });
+        truss.drag_nodes(egui::vec2(truss.view.scale, 0.0));
+        truss.undo();
+        assert!(truss.drag.is_none());
+        assert_eq!(truss.model.points[1], Point::new(1.0, 0.0));
+        truss.redo();
+        assert_eq!(truss.model.points[1], Point::new(2.0, 0.0));
+    }
+}
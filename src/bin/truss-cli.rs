@@ -0,0 +1,303 @@
+//! Solves a truss file without the editor and prints the results.
+//!
+//! ```text
+//! truss-cli [--joints] [--penalty] [--format table|json|csv] [--units si|us] FILE
+//! ```
+//!
+//! Tables and CSV are in the chosen units (SI with kN and MPa by default);
+//! JSON is always in SI base units.
+//!
+//! Exits with 0 when solved, 1 when the model is unstable and 2 for any
+//! other error.
+use std::fmt::Write as _;
+use std::process::ExitCode;
+
+use serde::Serialize;
+use truss::Model;
+use truss::TrussError;
+use truss::physics::{self, MemberResult, NodeDisplacement, Reaction, SolverMethod};
+use truss::units::UnitSystem;
+
+const USAGE: &str =
+    "usage: truss-cli [--joints] [--penalty] [--format table|json|csv] [--units si|us] FILE";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+#[derive(Debug, PartialEq)]
+struct Options {
+    path: String,
+    format: Format,
+    /// Solve by the method of joints instead of the stiffness method.
+    joints: bool,
+    penalty: bool,
+    units: UnitSystem,
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+    let mut path = None;
+    let mut format = Format::Table;
+    let mut joints = false;
+    let mut penalty = false;
+    let mut units = UnitSystem::si();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--joints" => joints = true,
+            "--penalty" => penalty = true,
+            "--format" => {
+                format = match args.next().as_deref() {
+                    Some("table") => Format::Table,
+                    Some("json") => Format::Json,
+                    Some("csv") => Format::Csv,
+                    other => return Err(format!("unknown format {other:?}")),
+                }
+            }
+            "--units" => {
+                let name = args.next().unwrap_or_default();
+                units = UnitSystem::preset(&name).ok_or(format!("unknown units {name:?}"))?;
+            }
+            flag if flag.starts_with("--") => return Err(format!("unknown option {flag}")),
+            _ if path.is_some() => return Err("only one file can be solved at a time".to_string()),
+            _ => path = Some(arg),
+        }
+    }
+    Ok(Options {
+        path: path.ok_or("no file given")?,
+        format,
+        joints,
+        penalty,
+        units,
+    })
+}
+
+/// What either solver produces, borrowed for printing. The method of joints
+/// has no displacements.
+struct Report<'a> {
+    members: &'a [MemberResult],
+    reactions: &'a [Reaction],
+    displacements: &'a [NodeDisplacement],
+    units: UnitSystem,
+}
+
+/// Column headers and rows of each block, converted to the report's units.
+impl Report<'_> {
+    fn blocks(&self) -> Vec<(Vec<String>, Vec<Vec<f64>>)> {
+        let u = self.units;
+        let mut blocks = vec![
+            (
+                vec![
+                    "member".to_string(),
+                    "p1".to_string(),
+                    "p2".to_string(),
+                    format!("length [{}]", u.length),
+                    format!("force [{}]", u.force),
+                    format!("stress [{}]", u.stress),
+                    "strain".to_string(),
+                ],
+                self.members
+                    .iter()
+                    .map(|m| {
+                        vec![
+                            m.member as f64,
+                            m.p1 as f64,
+                            m.p2 as f64,
+                            u.length.from_si(m.length),
+                            u.force.from_si(m.force),
+                            u.stress.from_si(m.stress),
+                            m.strain,
+                        ]
+                    })
+                    .collect(),
+            ),
+            (
+                vec![
+                    "node".to_string(),
+                    format!("rx [{}]", u.force),
+                    format!("ry [{}]", u.force),
+                ],
+                self.reactions
+                    .iter()
+                    .map(|r| vec![r.node as f64, u.force.from_si(r.rx), u.force.from_si(r.ry)])
+                    .collect(),
+            ),
+        ];
+        if !self.displacements.is_empty() {
+            blocks.push((
+                vec![
+                    "node".to_string(),
+                    format!("dx [{}]", u.length),
+                    format!("dy [{}]", u.length),
+                ],
+                self.displacements
+                    .iter()
+                    .map(|d| {
+                        vec![
+                            d.node as f64,
+                            u.length.from_si(d.dx),
+                            u.length.from_si(d.dy),
+                        ]
+                    })
+                    .collect(),
+            ));
+        }
+        blocks
+    }
+}
+
+/// Index columns print as integers; the rest in scientific notation.
+fn is_index(header: &str) -> bool {
+    matches!(header, "member" | "node" | "p1" | "p2")
+}
+
+fn table(report: &Report) -> String {
+    let mut out = String::new();
+    for (idx, (headers, rows)) in report.blocks().iter().enumerate() {
+        if idx > 0 {
+            out.push('\n');
+        }
+        let width = |h: &str| if is_index(h) { 6 } else { 14 };
+        let line: Vec<String> = headers
+            .iter()
+            .map(|h| format!("{h:>w$}", w = width(h)))
+            .collect();
+        writeln!(out, "{}", line.join(" ")).unwrap();
+        for row in rows {
+            let line: Vec<String> = headers
+                .iter()
+                .zip(row)
+                .map(|(h, v)| {
+                    if is_index(h) {
+                        format!("{:>w$}", *v as usize, w = width(h))
+                    } else {
+                        format!("{v:>w$.6e}", w = width(h))
+                    }
+                })
+                .collect();
+            writeln!(out, "{}", line.join(" ")).unwrap();
+        }
+    }
+    out
+}
+
+/// Member, reaction and displacement blocks, each with its own header and
+/// separated by a blank line.
+fn csv(report: &Report) -> String {
+    let mut out = String::new();
+    for (idx, (headers, rows)) in report.blocks().iter().enumerate() {
+        if idx > 0 {
+            out.push('\n');
+        }
+        writeln!(out, "{}", headers.join(",")).unwrap();
+        for row in rows {
+            let fields: Vec<String> = row.iter().map(f64::to_string).collect();
+            writeln!(out, "{}", fields.join(",")).unwrap();
+        }
+    }
+    out
+}
+
+fn render<S: Serialize>(format: Format, result: &S, report: Report) -> String {
+    match format {
+        Format::Table => table(&report),
+        Format::Csv => csv(&report),
+        Format::Json => {
+            serde_json::to_string_pretty(result).expect("results serialize to JSON") + "\n"
+        }
+    }
+}
+
+fn run(options: &Options) -> Result<String, TrussError> {
+    let mut model = Model::load(&options.path)?;
+    if options.penalty {
+        model.solver = SolverMethod::Penalty;
+    }
+    if options.joints {
+        let result = physics::calculate_member_stress(&model)?;
+        let report = Report {
+            members: &result.members,
+            reactions: &result.reactions,
+            displacements: &[],
+            units: options.units,
+        };
+        Ok(render(options.format, &result, report))
+    } else {
+        let result = physics::solve_stiff(&model)?;
+        let report = Report {
+            members: &result.members,
+            reactions: &result.reactions,
+            displacements: &result.displacements,
+            units: options.units,
+        };
+        Ok(render(options.format, &result, report))
+    }
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{err}\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match run(&options) {
+        Ok(out) => {
+            print!("{out}");
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("{}: {err}", options.path);
+            match err {
+                TrussError::Unstable(_) => ExitCode::from(1),
+                _ => ExitCode::from(2),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Result<Options, String> {
+        parse_args(line.split_whitespace().map(str::to_string))
+    }
+
+    #[test]
+    fn options_parse() {
+        assert_eq!(
+            args("--format csv --joints a.truss").unwrap(),
+            Options {
+                path: "a.truss".to_string(),
+                format: Format::Csv,
+                joints: true,
+                penalty: false,
+                units: UnitSystem::si(),
+            }
+        );
+        assert!(args("--format xml a.truss").is_err());
+        assert!(args("a.truss b.truss").is_err());
+        assert!(args("--penalty").is_err());
+        assert_eq!(args("--units us a.truss").unwrap().units, UnitSystem::us());
+        assert!(args("--units metric a.truss").is_err());
+    }
+
+    #[test]
+    fn csv_has_a_block_per_result() {
+        let options = args("--format csv test_trusses/truss1.json").unwrap();
+        let out = run(&options).unwrap();
+        let blocks: Vec<&str> = out.split("\n\n").collect();
+
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0].lines().count(), 1 + 3);
+        assert_eq!(blocks[1].lines().next(), Some("node,rx [kN],ry [kN]"));
+        // The 2 N load at the pin comes straight back as its reaction.
+        assert_eq!(blocks[1].lines().nth(1), Some("0,0,0.002"));
+        assert_eq!(blocks[2].lines().count(), 1 + 3);
+    }
+}
@@ -0,0 +1,70 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrussError {
+    /// The structure cannot carry the load: too few reactions or a mechanism.
+    Unstable(String),
+    /// Joint equilibrium alone cannot resolve `degree` redundant unknowns.
+    Indeterminate(usize),
+    /// A support was placed where there is no node.
+    SupportOffNode,
+    /// Member `index` starts and ends at the same location.
+    ZeroLengthMember(usize),
+    /// Force `index` has no direction.
+    ZeroLengthForce(usize),
+    /// `kind` number `index` refers to `node`, which does not exist.
+    DanglingIndex {
+        kind: &'static str,
+        index: usize,
+        node: usize,
+    },
+    /// Member `member` refers to a material or section that is not in the library.
+    MissingProperty {
+        member: usize,
+        kind: &'static str,
+        index: usize,
+    },
+    /// `kind` number `index` refers to a load case that does not exist.
+    MissingLoadCase {
+        kind: &'static str,
+        index: usize,
+        case: usize,
+    },
+    /// The linear solver rejected the stiffness matrix.
+    Factorization(String),
+    /// A truss file could not be read or written.
+    Io(String),
+    /// A truss file is not in the expected schema.
+    Format(String),
+}
+
+impl fmt::Display for TrussError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrussError::Unstable(reason) => write!(f, "structure is unstable: {reason}"),
+            TrussError::Indeterminate(degree) => write!(
+                f,
+                "structure is indeterminate to degree {degree}; use the stiffness solver"
+            ),
+            TrussError::SupportOffNode => write!(f, "supports must be placed on a node"),
+            TrussError::ZeroLengthMember(idx) => write!(f, "member {idx} has zero length"),
+            TrussError::ZeroLengthForce(idx) => write!(f, "force {idx} has no direction"),
+            TrussError::DanglingIndex { kind, index, node } => {
+                write!(f, "{kind} {index} refers to missing node {node}")
+            }
+            TrussError::MissingProperty {
+                member,
+                kind,
+                index,
+            } => write!(f, "member {member} refers to missing {kind} {index}"),
+            TrussError::MissingLoadCase { kind, index, case } => {
+                write!(f, "{kind} {index} refers to missing load case {case}")
+            }
+            TrussError::Factorization(msg) => write!(f, "factorization failed: {msg}"),
+            TrussError::Io(msg) => write!(f, "{msg}"),
+            TrussError::Format(msg) => write!(f, "not a truss file: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for TrussError {}
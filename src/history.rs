@@ -0,0 +1,285 @@
+use std::collections::VecDeque;
+
+use crate::physics::SolverMethod;
+use crate::{
+    Combination, ConnectionData, Entity, Force, Library, LoadCase, Member, Model, Point, Removed,
+    Settlement, Workspace,
+};
+
+/// Edits kept for undo. Older ones are forgotten.
+pub const HISTORY_LIMIT: usize = 100;
+
+/// One undoable change to a model.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Edit {
+    AddNode(Point),
+    AddMember(Member),
+    AddSupport(ConnectionData),
+    AddForce(Force),
+    AddCase(LoadCase),
+    AddCombination(Combination),
+    /// Member `usize` cut at the last node, which becomes its end. The part
+    /// beyond is added as a new member with the same properties.
+    SplitMember(usize),
+    /// Nodes moved, with their positions before and after.
+    MoveNodes(Vec<(usize, Point, Point)>),
+    /// Entities deleted together, and everything `Model::remove` took out
+    /// with them.
+    Remove(Vec<Entity>, Removed),
+    /// A part of the model set by a command, before and after.
+    Set(Part, Part),
+    /// Edits made by one keypress or command, applied in order.
+    Group(Vec<Edit>),
+}
+
+impl Edit {
+    /// The edit that sets `part` of `model`, or `None` if it already is.
+    pub fn set(model: &Model, part: Part) -> Option<Edit> {
+        let before = part.read(model);
+        (before != part).then_some(Edit::Set(before, part))
+    }
+
+    pub fn apply(&self, model: &mut Model) {
+        match self {
+            Edit::AddNode(point) => model.points.push(*point),
+            Edit::AddMember(member) => model.edges.push(member.clone()),
+            Edit::AddSupport(connection) => model.connections.push(connection.clone()),
+            Edit::AddForce(force) => model.force.push(force.clone()),
+            Edit::SplitMember(member) => {
+                let node = model.points.len() - 1;
+                let beyond = Member {
+                    p1: node,
+                    ..model.edges[*member].clone()
+                };
+                model.edges[*member].p2 = node;
+                model.edges.push(beyond);
+            }
+            Edit::MoveNodes(moves) => {
+                for (node, _, to) in moves {
+                    model.move_node(*node, *to);
+                }
+            }
+            Edit::Remove(entities, _) => {
+                model.remove(entities);
+            }
+            Edit::AddCase(case) => model.load_cases.push(case.clone()),
+            Edit::AddCombination(combination) => model.combinations.push(combination.clone()),
+            Edit::Set(_, after) => after.write(model),
+            Edit::Group(edits) => edits.iter().for_each(|edit| edit.apply(model)),
+        }
+    }
+
+    /// Undoes `apply`, on the model as `apply` left it.
+    pub fn revert(&self, model: &mut Model) {
+        match self {
+            Edit::AddNode(_) => {
+                model.points.pop();
+            }
+            Edit::AddMember(_) => {
+                model.edges.pop();
+            }
+            Edit::AddSupport(_) => {
+                model.connections.pop();
+            }
+            Edit::AddForce(_) => {
+                model.force.pop();
+            }
+            Edit::SplitMember(member) => {
+                if let Some(beyond) = model.edges.pop() {
+                    model.edges[*member].p2 = beyond.p2;
+                }
+            }
+            Edit::MoveNodes(moves) => {
+                for (node, from, _) in moves {
+                    model.move_node(*node, *from);
+                }
+            }
+            Edit::Remove(_, removed) => model.restore(removed),
+            Edit::AddCase(_) => {
+                model.load_cases.pop();
+            }
+            Edit::AddCombination(_) => {
+                model.combinations.pop();
+            }
+            Edit::Set(before, _) => before.write(model),
+            Edit::Group(edits) => edits.iter().rev().for_each(|edit| edit.revert(model)),
+        }
+    }
+}
+
+/// A piece of the model that a command sets as a whole.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Part {
+    Library(Library),
+    Settlements(Vec<Settlement>),
+    Solver(SolverMethod),
+    Workspace(Workspace),
+    Combination(usize, Combination),
+    Member(usize, Member),
+    Support(usize, ConnectionData),
+}
+
+impl Part {
+    /// The same part as it is in `model`.
+    fn read(&self, model: &Model) -> Part {
+        match self {
+            Part::Library(_) => Part::Library(model.library.clone()),
+            Part::Settlements(_) => Part::Settlements(model.settlements.clone()),
+            Part::Solver(_) => Part::Solver(model.solver),
+            Part::Workspace(_) => Part::Workspace(model.workspace.clone()),
+            Part::Combination(idx, _) => Part::Combination(*idx, model.combinations[*idx].clone()),
+            Part::Member(idx, _) => Part::Member(*idx, model.edges[*idx].clone()),
+            Part::Support(idx, _) => Part::Support(*idx, model.connections[*idx].clone()),
+        }
+    }
+
+    fn write(&self, model: &mut Model) {
+        match self {
+            Part::Library(library) => model.library = library.clone(),
+            Part::Settlements(settlements) => model.settlements = settlements.clone(),
+            Part::Solver(solver) => model.solver = *solver,
+            Part::Workspace(workspace) => model.workspace = workspace.clone(),
+            Part::Combination(idx, combination) => model.combinations[*idx] = combination.clone(),
+            Part::Member(idx, member) => model.edges[*idx] = member.clone(),
+            Part::Support(idx, connection) => model.connections[*idx] = connection.clone(),
+        }
+    }
+}
+
+/// Edits made to a model, for undo and redo. Every change to the model has
+/// to go through here for the history to stay consistent with it.
+#[derive(Debug, Default)]
+pub struct History {
+    done: VecDeque<Edit>,
+    undone: Vec<Edit>,
+}
+
+impl History {
+    /// Applies `edit` to `model` and records it.
+    pub fn apply(&mut self, model: &mut Model, edit: Edit) {
+        edit.apply(model);
+        self.record(edit);
+    }
+
+    /// Records an edit already made to the model. Anything undone can no
+    /// longer be redone.
+    pub fn record(&mut self, edit: Edit) {
+        self.undone.clear();
+        self.done.push_back(edit);
+        if self.done.len() > HISTORY_LIMIT {
+            self.done.pop_front();
+        }
+    }
+
+    /// Reverts the last edit. Returns false if there is nothing to undo.
+    pub fn undo(&mut self, model: &mut Model) -> bool {
+        let Some(edit) = self.done.pop_back() else {
+            return false;
+        };
+        edit.revert(model);
+        self.undone.push(edit);
+        true
+    }
+
+    /// Reapplies the last undone edit. Returns false if there is nothing to
+    /// redo.
+    pub fn redo(&mut self, model: &mut Model) -> bool {
+        let Some(edit) = self.undone.pop() else {
+            return false;
+        };
+        edit.apply(model);
+        self.done.push_back(edit);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn undo_and_redo_retrace_the_edits() {
+        let mut model = Model::default();
+        let mut history = History::default();
+        history.apply(&mut model, Edit::AddNode(Point::new(0.0, 0.0)));
+        history.apply(
+            &mut model,
+            Edit::Group(vec![
+                Edit::AddNode(Point::new(2.0, 0.0)),
+                Edit::AddMember(Member::new(0, 1)),
+            ]),
+        );
+        history.apply(
+            &mut model,
+            Edit::MoveNodes(vec![(1, Point::new(2.0, 0.0), Point::new(3.0, 1.0))]),
+        );
+        let drawn = model.clone();
+
+        assert!(history.undo(&mut model));
+        assert_eq!(model.points[1], Point::new(2.0, 0.0));
+        assert!(history.undo(&mut model));
+        assert_eq!((model.points.len(), model.edges.len()), (1, 0));
+        assert!(history.redo(&mut model));
+        assert!(history.redo(&mut model));
+        assert_eq!(model, drawn);
+        assert!(!history.redo(&mut model));
+
+        // A new edit after an undo drops what was undone.
+        history.undo(&mut model);
+        history.apply(&mut model, Edit::AddSupport(ConnectionData::Pin(0)));
+        assert!(!history.redo(&mut model));
+    }
+
+    #[test]
+    fn splitting_a_member_is_undone_exactly() {
+        let mut model = Model::default();
+        let mut history = History::default();
+        history.apply(&mut model, Edit::AddNode(Point::new(0.0, 0.0)));
+        history.apply(&mut model, Edit::AddNode(Point::new(4.0, 0.0)));
+        history.apply(&mut model, Edit::AddMember(Member::new(0, 1)));
+        let drawn = model.clone();
+
+        history.apply(
+            &mut model,
+            Edit::Group(vec![
+                Edit::AddNode(Point::new(2.0, 0.0)),
+                Edit::SplitMember(0),
+            ]),
+        );
+        let ends: Vec<_> = model.edges.iter().map(|m| (m.p1, m.p2)).collect();
+        assert_eq!(ends, [(0, 2), (2, 1)]);
+
+        history.undo(&mut model);
+        assert_eq!(model, drawn);
+    }
+
+    #[test]
+    fn setting_a_part_keeps_only_that_part() {
+        let mut model = Model::default();
+        let mut history = History::default();
+        let mut library = model.library.clone();
+        library.sections[0].area = 0.5;
+        let edit = Edit::set(&model, Part::Library(library.clone())).unwrap();
+        assert!(matches!(
+            &edit,
+            Edit::Set(Part::Library(_), Part::Library(_))
+        ));
+        history.apply(&mut model, edit);
+        assert_eq!(model.library, library);
+        assert_eq!(Edit::set(&model, Part::Library(library)), None);
+
+        history.undo(&mut model);
+        assert_eq!(model, Model::default());
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let mut model = Model::default();
+        let mut history = History::default();
+        for idx in 0..HISTORY_LIMIT + 5 {
+            history.apply(&mut model, Edit::AddNode(Point::new(idx as f32, 0.0)));
+        }
+        while history.undo(&mut model) {}
+        assert_eq!(model.points.len(), 5);
+    }
+}
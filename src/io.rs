@@ -0,0 +1,238 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::ConnectionData;
+use crate::Force;
+use crate::Member;
+use crate::Model;
+use crate::Point;
+use crate::TrussError;
+use crate::physics::validate;
+use serde::{Deserialize, Serialize};
+
+/// Drawing area a truss file was made in, in model units unless noted.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Workspace {
+    #[serde(rename = "workspace-width")]
+    pub width: f32,
+    #[serde(rename = "workspace-height")]
+    pub height: f32,
+    #[serde(rename = "workspace-width-pixels")]
+    pub width_pixels: f32,
+    /// Distance from the left edge to the y axis.
+    #[serde(rename = "Yaxis-dist-from-left")]
+    pub yaxis_from_left: f32,
+    /// Distance from the bottom edge to the x axis.
+    #[serde(rename = "Xaxis-dist-from-bottom")]
+    pub xaxis_from_bottom: f32,
+    #[serde(rename = "grid-x")]
+    pub grid_x: f32,
+    #[serde(rename = "grid-y")]
+    pub grid_y: f32,
+    /// Force per model unit of drawn arrow length. Kept for the file
+    /// format; the editor scales arrows to the largest load instead.
+    #[serde(rename = "force-scale")]
+    pub force_scale: f32,
+}
+
+impl Default for Workspace {
+    fn default() -> Self {
+        Workspace {
+            width: 30.0,
+            height: 18.0,
+            width_pixels: 926.0,
+            yaxis_from_left: 15.0,
+            xaxis_from_bottom: 6.0,
+            grid_x: 1.0,
+            grid_y: 1.0,
+            force_scale: 100.0,
+        }
+    }
+}
+
+/// A truss file as stored: nodes as `"x,y"`, members as `"p1,p2"`, supports
+/// keyed by node, and forces as `"node,fx,fy"`.
+#[derive(Debug, Serialize, Deserialize)]
+struct RawTruss {
+    nodes: Vec<String>,
+    members: Vec<String>,
+    supports: BTreeMap<String, String>,
+    forces: Vec<String>,
+    #[serde(default)]
+    workspace: Workspace,
+}
+
+/// Parses a comma separated record with exactly `N` fields.
+fn fields<T: std::str::FromStr, const N: usize>(
+    kind: &str,
+    record: &str,
+) -> Result<[T; N], TrussError> {
+    let bad = || TrussError::Format(format!("bad {kind} \"{record}\""));
+    let parsed: Vec<T> = record
+        .split(',')
+        .map(|x| x.trim().parse().map_err(|_| bad()))
+        .collect::<Result<_, _>>()?;
+    parsed.try_into().map_err(|_| bad())
+}
+
+impl Model {
+    /// Parses a truss in the `test_trusses` JSON schema. Member properties
+    /// are not stored there, so every member gets the first material and
+    /// section.
+    pub fn from_json(json: &str) -> Result<Model, TrussError> {
+        let raw = serde_json::from_str(json).map_err(|err| TrussError::Format(err.to_string()))?;
+        Model::from_raw(raw)
+    }
+
+    /// Like `from_json`, for a file that has already been parsed.
+    pub(crate) fn from_json_value(value: serde_json::Value) -> Result<Model, TrussError> {
+        let raw =
+            serde_json::from_value(value).map_err(|err| TrussError::Format(err.to_string()))?;
+        Model::from_raw(raw)
+    }
+
+    fn from_raw(raw: RawTruss) -> Result<Model, TrussError> {
+        let mut points = Vec::with_capacity(raw.nodes.len());
+        for node in &raw.nodes {
+            let [x, y] = fields::<f32, 2>("node", node)?;
+            points.push(Point::new(x, y));
+        }
+        let mut edges = Vec::with_capacity(raw.members.len());
+        for member in &raw.members {
+            let [p1, p2] = fields::<usize, 2>("member", member)?;
+            edges.push(Member::new(p1, p2));
+        }
+        let mut connections = Vec::with_capacity(raw.supports.len());
+        for (node, code) in &raw.supports {
+            let connection = node
+                .parse()
+                .ok()
+                .and_then(|node| ConnectionData::from_code(node, code))
+                .ok_or_else(|| TrussError::Format(format!("bad support \"{node}\": \"{code}\"")))?;
+            connections.push(connection);
+        }
+        let mut force = Vec::with_capacity(raw.forces.len());
+        for record in &raw.forces {
+            let [node, fx, fy] = fields::<f32, 3>("force", record)?;
+            let p1 = node as usize;
+            if p1 as f32 != node || p1 >= points.len() {
+                return Err(TrussError::Format(format!("bad force \"{record}\"")));
+            }
+            force.push(Force {
+                p1,
+                p2: Point::new(points[p1].x + fx, points[p1].y + fy),
+                mag: fx.hypot(fy),
+                case: 0,
+            });
+        }
+
+        let model = Model {
+            points,
+            edges,
+            connections,
+            force,
+            workspace: raw.workspace,
+            ..Default::default()
+        };
+        validate(&model)?;
+        Ok(model)
+    }
+
+    /// Writes the model in the `test_trusses` JSON schema. Only one support
+    /// per node can be stored; a later one at the same node replaces it.
+    /// The schema has no load cases, so forces of every case are written as
+    /// one and combinations are dropped.
+    pub fn to_json(&self) -> String {
+        let raw = RawTruss {
+            nodes: self
+                .points
+                .iter()
+                .map(|p| format!("{},{}", p.x, p.y))
+                .collect(),
+            members: self
+                .edges
+                .iter()
+                .map(|m| format!("{},{}", m.p1, m.p2))
+                .collect(),
+            supports: self
+                .connections
+                .iter()
+                .map(|c| (c.node().to_string(), c.code()))
+                .collect(),
+            forces: self
+                .force
+                .iter()
+                .map(|f| {
+                    let start = self.points[f.p1];
+                    let scale = f.mag / start.distance(f.p2);
+                    let fx = (f.p2.x - start.x) * scale;
+                    let fy = (f.p2.y - start.y) * scale;
+                    format!("{},{fx},{fy}", f.p1)
+                })
+                .collect(),
+            workspace: self.workspace.clone(),
+        };
+        serde_json::to_string_pretty(&raw).expect("truss files contain only strings and numbers")
+    }
+
+    pub fn load_json<P: AsRef<Path>>(path: P) -> Result<Model, TrussError> {
+        let json = fs::read_to_string(&path)
+            .map_err(|err| TrussError::Io(format!("{}: {err}", path.as_ref().display())))?;
+        Model::from_json(&json)
+    }
+
+    pub fn save_json<P: AsRef<Path>>(&self, path: P) -> Result<(), TrussError> {
+        fs::write(&path, self.to_json())
+            .map_err(|err| TrussError::Io(format!("{}: {err}", path.as_ref().display())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truss_round_trips() {
+        let truss = Model::load_json("test_trusses/truss1.json").unwrap();
+        assert_eq!(truss.points.len(), 3);
+        assert_eq!((truss.edges[0].p1, truss.edges[0].p2), (2, 0));
+        assert!(matches!(
+            truss.connections[1],
+            ConnectionData::Roller(1, 90.0)
+        ));
+        assert_eq!(truss.force[0].p2, Point::new(0.0, -2.0));
+        assert_eq!(truss.force[0].mag, 2.0);
+
+        let again = Model::from_json(&truss.to_json()).unwrap();
+        assert_eq!(again.points, truss.points);
+        assert_eq!(again.force[0].p2, truss.force[0].p2);
+        assert_eq!(again.workspace, truss.workspace);
+        assert_eq!(
+            again
+                .connections
+                .iter()
+                .map(|c| c.code())
+                .collect::<Vec<_>>(),
+            ["P", "Rh"]
+        );
+    }
+
+    #[test]
+    fn malformed_files_are_rejected() {
+        let json = r#"{"nodes": ["0,0", "1"], "members": [], "supports": {}, "forces": []}"#;
+        assert_eq!(
+            Model::from_json(json).unwrap_err(),
+            TrussError::Format("bad node \"1\"".to_string())
+        );
+
+        let json = r#"{"nodes": ["0,0", "1,0"], "members": ["0,2"], "supports": {}, "forces": []}"#;
+        assert!(matches!(
+            Model::from_json(json),
+            Err(TrussError::DanglingIndex { kind: "member", .. })
+        ));
+
+        let json = r#"{"nodes": ["0,0"], "members": [], "supports": {"0": "X"}, "forces": []}"#;
+        assert!(matches!(Model::from_json(json), Err(TrussError::Format(_))));
+    }
+}
@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+
+/// Elastic properties of a member material, in SI units (Pa).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Material {
+    pub name: String,
+    pub youngs: f64,
+    #[serde(with = "unlimited")]
+    pub yield_stress: f64,
+}
+
+/// JSON has no infinity, so a material without a yield stress stores `null`.
+mod unlimited {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+        if value.is_finite() {
+            serializer.serialize_some(value)
+        } else {
+            serializer.serialize_none()
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        Ok(Option::<f64>::deserialize(deserializer)?.unwrap_or(f64::INFINITY))
+    }
+}
+
+impl Material {
+    pub fn aluminium() -> Self {
+        Material {
+            name: "aluminium".to_string(),
+            youngs: 69e9,
+            yield_stress: 276e6,
+        }
+    }
+
+    pub fn steel() -> Self {
+        Material {
+            name: "steel".to_string(),
+            youngs: 200e9,
+            yield_stress: 250e6,
+        }
+    }
+}
+
+/// Cross-section of a member. Only the area matters for an axial element.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Section {
+    pub name: String,
+    pub area: f64,
+}
+
+impl Default for Section {
+    fn default() -> Self {
+        Section {
+            name: "default".to_string(),
+            area: 2.0,
+        }
+    }
+}
+
+/// Materials and sections that members refer to by index.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Library {
+    pub materials: Vec<Material>,
+    pub sections: Vec<Section>,
+}
+
+impl Default for Library {
+    fn default() -> Self {
+        Library {
+            materials: vec![Material::aluminium(), Material::steel()],
+            sections: vec![Section::default()],
+        }
+    }
+}
+
+impl Library {
+    pub fn material_index(&self, name: &str) -> Option<usize> {
+        self.materials.iter().position(|m| m.name == name)
+    }
+
+    pub fn section_index(&self, name: &str) -> Option<usize> {
+        self.sections.iter().position(|s| s.name == name)
+    }
+
+    /// Adds `material`, replacing any existing one with the same name.
+    /// Returns its index.
+    pub fn set_material(&mut self, material: Material) -> usize {
+        match self.material_index(&material.name) {
+            Some(idx) => {
+                self.materials[idx] = material;
+                idx
+            }
+            None => {
+                self.materials.push(material);
+                self.materials.len() - 1
+            }
+        }
+    }
+
+    /// Adds `section`, replacing any existing one with the same name.
+    /// Returns its index.
+    pub fn set_section(&mut self, section: Section) -> usize {
+        match self.section_index(&section.name) {
+            Some(idx) => {
+                self.sections[idx] = section;
+                idx
+            }
+            None => {
+                self.sections.push(section);
+                self.sections.len() - 1
+            }
+        }
+    }
+}
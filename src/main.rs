@@ -57,41 +57,17 @@ fn main() {
 }
 #[cfg(test)]
 mod tests {
-    use std::f32::consts::PI;
-
-    use super::*;
-    use egui::debug_text::print;
-    use faer::Mat;
-    use faer::prelude::*;
-    use faer::traits::num_traits::ToPrimitive;
-    fn zeros(mut matrix: Mat<f32>) -> Mat<f32> {
-        for i in 0..matrix.nrows() {
-            for j in 0..matrix.ncols() {
-                if matrix[(i, j)].abs() < 1e-4 {
-                    matrix[(i, j)] = 0.;
-                }
-            }
-        }
-        matrix
-    }
-
-    use egui::Pos2;
-    use rayon::str::SplitInclusive;
+    use nalgebra::DMatrix as Mat;
     use std::fs;
     use std::path::Path;
     use truss::ConnectionData;
-    use truss::Force;
-    use truss::Member;
-    use truss::Truss;
+    use truss::Model;
+    use truss::TrussError;
 
+    /// Expected member forces and reaction components stored alongside a test truss.
     #[derive(Debug, serde::Deserialize)]
-    struct RawTruss {
-        nodes: Vec<String>,
-        members: Vec<String>,
-        supports: serde_json::Map<String, serde_json::Value>,
-        forces: Vec<String>,
-        workspace: serde_json::Value,
-        awnsers: Vec<f32>,
+    struct Answers {
+        awnsers: Vec<f64>,
     }
 
     pub fn load_trusses_from_folder<P: AsRef<Path>>(folder: P) -> Vec<bool> {
@@ -104,103 +80,46 @@ mod tests {
 
             if file_name.starts_with("truss") {
                 let data = fs::read_to_string(&path).unwrap();
-                let raw: RawTruss = serde_json::from_str(&data).unwrap();
-
-                // Parse nodes/
-                let nodes: Vec<Pos2> = raw
-                    .nodes
-                    .iter()
-                    .map(|(s)| {
-                        let parts: Vec<f32> = s
-                            .split(',')
-                            .map(|x| x.trim().parse::<f32>().unwrap())
-                            .collect();
-                        Pos2 {
-                            x: parts[0],
-                            y: parts[1],
+                let raw: Answers = serde_json::from_str(&data).unwrap();
+                let truss = Model::from_json(&data).unwrap();
+                let matches = match truss::physics::calculate_member_stress(&truss) {
+                    // Answers list member forces, then each support's reaction
+                    // components in the order the supports are listed.
+                    Ok(joints) => {
+                        let mut expirimental: Vec<f64> =
+                            joints.members.iter().map(|m| m.force).collect();
+                        for connection in &truss.connections {
+                            let reaction = joints.reaction(connection.node()).unwrap();
+                            match connection {
+                                ConnectionData::Pin(_) => {
+                                    expirimental.push(reaction.rx);
+                                    expirimental.push(reaction.ry);
+                                }
+                                ConnectionData::Roller(_, angle) => {
+                                    let angle = f64::from(*angle).to_radians();
+                                    expirimental.push(
+                                        reaction.rx * angle.cos() + reaction.ry * angle.sin(),
+                                    );
+                                }
+                            }
                         }
-                    })
-                    .collect();
-
-                // Parse members into your Member struct
-                let edges: Vec<Member> = raw
-                    .members
-                    .iter()
-                    .enumerate()
-                    .map(|(i, s)| {
-                        let parts: Vec<usize> = s
-                            .split(',')
-                            .map(|x| x.trim().parse::<usize>().unwrap())
-                            .collect();
-                        let start = parts[0];
-                        let end = parts[1];
-                        Member { p1: start, p2: end }
-                    })
-                    .collect();
-
-                // TODO add connections
-                let connections: Vec<ConnectionData> = raw
-                    .supports
-                    .iter()
-                    .map(|(idx, typ)| {
-                        if typ == "P" {
-                            println!("Pin at {}", idx);
-                            ConnectionData::Pin(idx.parse::<usize>().unwrap())
-                        } else {
-                            println!("Roller at {}", idx);
-                            ConnectionData::Roller(idx.parse::<usize>().unwrap())
-                        }
-                    })
-                    .collect();
-                let forces = raw
-                    .forces
-                    .iter()
-                    .map(|s| {
-                        let parts: Vec<f32> = s
-                            .split(',')
-                            .map(|x| x.trim().parse::<f32>().unwrap())
-                            .collect();
-                        let p1x = nodes[parts[0].to_usize().unwrap()].x;
-
-                        let p1y = nodes[parts[0].to_usize().unwrap()].y;
-                        let p2 = Pos2::new((parts[1] + p1x), (parts[2] + p1y));
-                        let mag = Pos2::new(p1x, p1y).distance(p2);
-                        println!("mag{}, p2{}", mag, p2);
-                        Force {
-                            p1: parts[0].to_usize().unwrap(),
-                            p2: p2,
-                            mag: mag,
-                        }
-                    })
-                    .collect();
-                let mut truss = Truss {
-                    force: forces,
-                    last_node: None,
-                    mode: truss::Mode::Insert,
-                    input_buf: String::new(),
-                    messagetyp: truss::MessageType::Forcemsg,
-                    points: nodes,
-
-                    edges,
-                    connections: connections,
+                        let expirimentalr = Mat::from_vec(expirimental.len(), 1, expirimental)
+                            .map(|x| x.round() as i64);
+                        let awnser_mat = Mat::from_fn(raw.awnsers.len(), 1, |i, _j| raw.awnsers[i])
+                            .map(|x| x.round() as i64);
+                        awnser_mat == expirimentalr
+                    }
+                    // Joint equilibrium cannot resolve these, so there is no
+                    // answer to compare; the stiffness solution must balance.
+                    Err(TrussError::Indeterminate(_)) => truss::physics::solve_stiff(&truss)
+                        .is_ok_and(|result| result.diagnostics.equilibrium.max_abs() < 1e-6),
+                    Err(_) => false,
                 };
-                let mut expirimental = truss::calculate_member_stress(&mut truss);
-                let mut expirimentalr = expirimental.map(|x| *x as i64);
-                let awnser_mat =
-                    Mat::from_fn(raw.awnsers.len(), 1, |i, j| raw.awnsers[i]).map(|x| *x as i64);
-                if awnser_mat == expirimentalr {
-                    vecbool.push(true);
-                } else {
-                    vecbool.push(false);
-                }
+                vecbool.push(matches);
             }
         }
         vecbool
     }
-    #[derive(serde::Deserialize)]
-    struct MatrixWrapper {
-        matrix: Vec<Vec<f32>>,
-    }
     //
     // fn test_triangle() -> Mat<f32> {
     //     let mut truss = Truss {
@@ -254,7 +173,7 @@ mod tests {
     //     truss
     //         .connections
     //         .append(&mut vec![p1.clone(), r1.clone(), f1.clone()]);
-    //     truss::calculate_member_stress(&mut truss)
+    //     truss::physics::calculate_member_stress(&mut truss)
     // }
 
     // fn basic_triangle() -> Mat<f32> {
@@ -0,0 +1,416 @@
+use crate::Library;
+use crate::Workspace;
+use crate::physics::SolverMethod;
+use serde::{Deserialize, Serialize};
+
+/// A location in model coordinates.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Point {
+    pub const fn new(x: f32, y: f32) -> Self {
+        Point { x, y }
+    }
+
+    pub fn distance(self, other: Point) -> f32 {
+        (self.x - other.x).hypot(self.y - other.y)
+    }
+}
+
+/// A load applied at node `p1`, pointing from it toward `p2`.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Force {
+    pub p1: usize,
+    pub p2: Point,
+    pub mag: f32,
+    /// Index into `Model::load_cases`.
+    #[serde(default)]
+    pub case: usize,
+}
+
+/// A named group of loads analysed together, such as dead, live or wind.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoadCase {
+    pub name: String,
+}
+
+/// Load cases added together with factors, such as 1.2D + 1.6L.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Combination {
+    pub name: String,
+    /// (load case, factor) pairs.
+    pub factors: Vec<(usize, f64)>,
+}
+
+/// Prescribed displacement of a supported node. Only the directions the
+/// support restrains are imposed.
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Settlement {
+    pub node: usize,
+    pub dx: f32,
+    pub dy: f32,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Member {
+    pub p1: usize,
+    pub p2: usize,
+    /// Index into `Library::materials`.
+    pub material: usize,
+    /// Index into `Library::sections`.
+    pub section: usize,
+}
+
+impl Member {
+    /// A member between two nodes using the first material and section.
+    pub fn new(p1: usize, p2: usize) -> Self {
+        Member {
+            p1,
+            p2,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ConnectionData {
+    /// Roller at a node, restraining only the direction given in degrees
+    /// from the +x axis. 90° is a roller on a horizontal surface.
+    Roller(usize, f32),
+    Pin(usize),
+}
+
+impl ConnectionData {
+    pub fn node(&self) -> usize {
+        match self {
+            ConnectionData::Roller(node, _) | ConnectionData::Pin(node) => *node,
+        }
+    }
+
+    fn node_mut(&mut self) -> &mut usize {
+        match self {
+            ConnectionData::Roller(node, _) | ConnectionData::Pin(node) => node,
+        }
+    }
+
+    /// Parses a support code from the truss files: `P` for a pin, `Rh` and
+    /// `Rv` for rollers on horizontal and vertical surfaces, and `R<deg>`
+    /// for a roller restraining an arbitrary direction.
+    pub fn from_code(node: usize, code: &str) -> Option<Self> {
+        match code {
+            "P" => Some(ConnectionData::Pin(node)),
+            "Rh" => Some(ConnectionData::Roller(node, 90.0)),
+            "Rv" => Some(ConnectionData::Roller(node, 0.0)),
+            _ => code
+                .strip_prefix('R')?
+                .parse()
+                .ok()
+                .filter(|angle: &f32| angle.is_finite())
+                .map(|angle| ConnectionData::Roller(node, angle)),
+        }
+    }
+
+    pub fn code(&self) -> String {
+        match self {
+            ConnectionData::Pin(_) => "P".to_string(),
+            ConnectionData::Roller(_, angle) if angle.rem_euclid(180.0) == 90.0 => "Rh".to_string(),
+            ConnectionData::Roller(_, angle) if angle.rem_euclid(180.0) == 0.0 => "Rv".to_string(),
+            ConnectionData::Roller(_, angle) => format!("R{angle}"),
+        }
+    }
+}
+
+/// The structure and everything needed to analyse it, independent of how
+/// it is edited or drawn.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Model {
+    /// Node positions in world units, y up.
+    pub points: Vec<Point>,
+    pub edges: Vec<Member>,
+    pub connections: Vec<ConnectionData>,
+    pub force: Vec<Force>,
+    #[serde(default)]
+    pub settlements: Vec<Settlement>,
+    #[serde(default)]
+    pub library: Library,
+    #[serde(default)]
+    pub solver: SolverMethod,
+    #[serde(default)]
+    pub workspace: Workspace,
+    /// Named load cases. Without any, every force is in one unnamed case.
+    #[serde(default)]
+    pub load_cases: Vec<LoadCase>,
+    #[serde(default)]
+    pub combinations: Vec<Combination>,
+}
+
+impl Model {
+    /// Number of load cases forces can be in, including the unnamed one of
+    /// a model without named cases.
+    pub fn case_count(&self) -> usize {
+        self.load_cases.len().max(1)
+    }
+
+    pub fn case_name(&self, case: usize) -> &str {
+        self.load_cases
+            .get(case)
+            .map_or("loads", |c| c.name.as_str())
+    }
+
+    pub fn case_index(&self, name: &str) -> Option<usize> {
+        self.load_cases.iter().position(|c| c.name == name)
+    }
+
+    /// Adds a load case called `name` unless there is one. Returns its index.
+    pub fn set_case(&mut self, name: &str) -> usize {
+        self.case_index(name).unwrap_or_else(|| {
+            self.load_cases.push(LoadCase {
+                name: name.to_string(),
+            });
+            self.load_cases.len() - 1
+        })
+    }
+
+    /// Adds `combination`, replacing any existing one with the same name.
+    /// Returns its index.
+    pub fn set_combination(&mut self, combination: Combination) -> usize {
+        match (self.combinations.iter()).position(|c| c.name == combination.name) {
+            Some(idx) => {
+                self.combinations[idx] = combination;
+                idx
+            }
+            None => {
+                self.combinations.push(combination);
+                self.combinations.len() - 1
+            }
+        }
+    }
+}
+
+/// A part of the model that can be selected and deleted on its own.
+/// Supports and forces are indices into `connections` and `force`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Entity {
+    Node(usize),
+    Member(usize),
+    Support(usize),
+    Force(usize),
+}
+
+/// What `Model::remove` took out, at the indices and with the node numbers
+/// it had, so that `Model::restore` can put it back.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Removed {
+    pub points: Vec<(usize, Point)>,
+    pub edges: Vec<(usize, Member)>,
+    pub connections: Vec<(usize, ConnectionData)>,
+    pub force: Vec<(usize, Force)>,
+    pub settlements: Vec<(usize, Settlement)>,
+}
+
+impl Model {
+    /// Moves `node` to `to`, carrying the forces on it along so that they
+    /// keep their direction.
+    pub fn move_node(&mut self, node: usize, to: Point) {
+        let from = self.points[node];
+        let (dx, dy) = (to.x - from.x, to.y - from.y);
+        self.points[node] = to;
+        for force in self.force.iter_mut().filter(|force| force.p1 == node) {
+            force.p2 = Point::new(force.p2.x + dx, force.p2.y + dy);
+        }
+    }
+
+    /// Removes `entities`, along with the members, supports, forces and
+    /// settlements of every removed node, and renumbers what is left.
+    pub fn remove(&mut self, entities: &[Entity]) -> Removed {
+        let mut removed = Removed::default();
+        let selected = |entity| entities.contains(&entity);
+        let mut renumbered = Vec::with_capacity(self.points.len());
+        let mut kept = 0;
+        for node in 0..self.points.len() {
+            if selected(Entity::Node(node)) {
+                renumbered.push(None);
+            } else {
+                renumbered.push(Some(kept));
+                kept += 1;
+            }
+        }
+        let node = |idx: usize| renumbered.get(idx).copied().flatten();
+
+        retain_indexed(&mut self.points, |idx, point| {
+            node(idx).is_some() || take(&mut removed.points, idx, point)
+        });
+        retain_indexed(&mut self.edges, |idx, member| {
+            match (
+                selected(Entity::Member(idx)),
+                node(member.p1),
+                node(member.p2),
+            ) {
+                (false, Some(p1), Some(p2)) => {
+                    (member.p1, member.p2) = (p1, p2);
+                    true
+                }
+                _ => take(&mut removed.edges, idx, member),
+            }
+        });
+        retain_indexed(&mut self.connections, |idx, connection| {
+            match (selected(Entity::Support(idx)), node(connection.node())) {
+                (false, Some(new)) => {
+                    *connection.node_mut() = new;
+                    true
+                }
+                _ => take(&mut removed.connections, idx, connection),
+            }
+        });
+        retain_indexed(&mut self.force, |idx, force| {
+            match (selected(Entity::Force(idx)), node(force.p1)) {
+                (false, Some(p1)) => {
+                    force.p1 = p1;
+                    true
+                }
+                _ => take(&mut removed.force, idx, force),
+            }
+        });
+        retain_indexed(&mut self.settlements, |idx, settlement| {
+            match node(settlement.node) {
+                Some(new) => {
+                    settlement.node = new;
+                    true
+                }
+                None => take(&mut removed.settlements, idx, settlement),
+            }
+        });
+        removed
+    }
+
+    /// Undoes the `remove` that returned `removed`, on the model as it left
+    /// it.
+    pub fn restore(&mut self, removed: &Removed) {
+        let mut original = Vec::with_capacity(self.points.len());
+        let mut gone = removed.points.iter().map(|(idx, _)| *idx).peekable();
+        for old in 0..self.points.len() + removed.points.len() {
+            if gone.next_if_eq(&old).is_none() {
+                original.push(old);
+            }
+        }
+        let node = |idx: &mut usize| *idx = original[*idx];
+        for member in &mut self.edges {
+            node(&mut member.p1);
+            node(&mut member.p2);
+        }
+        for connection in &mut self.connections {
+            node(connection.node_mut());
+        }
+        for force in &mut self.force {
+            node(&mut force.p1);
+        }
+        for settlement in &mut self.settlements {
+            node(&mut settlement.node);
+        }
+
+        insert_indexed(&mut self.points, &removed.points);
+        insert_indexed(&mut self.edges, &removed.edges);
+        insert_indexed(&mut self.connections, &removed.connections);
+        insert_indexed(&mut self.force, &removed.force);
+        insert_indexed(&mut self.settlements, &removed.settlements);
+    }
+}
+
+/// Records `item`, at `idx`, as removed. Returns false, for `retain`.
+fn take<T: Clone>(removed: &mut Vec<(usize, T)>, idx: usize, item: &T) -> bool {
+    removed.push((idx, item.clone()));
+    false
+}
+
+/// Puts `items` back at their indices, which must be in increasing order.
+fn insert_indexed<T: Clone>(items: &mut Vec<T>, removed: &[(usize, T)]) {
+    for (idx, item) in removed {
+        items.insert(*idx, item.clone());
+    }
+}
+
+/// `Vec::retain_mut` with the original index of each item.
+fn retain_indexed<T>(items: &mut Vec<T>, mut keep: impl FnMut(usize, &mut T) -> bool) {
+    let mut idx = 0;
+    items.retain_mut(|item| {
+        idx += 1;
+        keep(idx - 1, item)
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn support_codes_round_trip() {
+        for code in ["P", "Rh", "Rv", "R30"] {
+            assert_eq!(ConnectionData::from_code(4, code).unwrap().code(), code);
+        }
+        assert!(matches!(
+            ConnectionData::from_code(2, "Rv"),
+            Some(ConnectionData::Roller(2, 0.0))
+        ));
+        assert!(ConnectionData::from_code(0, "X").is_none());
+        assert!(ConnectionData::from_code(0, "Rinf").is_none());
+        assert!(ConnectionData::from_code(0, "RNaN").is_none());
+    }
+
+    #[test]
+    fn removing_a_node_renumbers_everything_after_it() {
+        let mut model = Model {
+            points: vec![
+                Point::new(0.0, 0.0),
+                Point::new(1.0, 0.0),
+                Point::new(2.0, 0.0),
+                Point::new(1.0, 1.0),
+            ],
+            edges: vec![
+                Member::new(0, 1),
+                Member::new(1, 2),
+                Member::new(2, 3),
+                Member::new(3, 0),
+            ],
+            connections: vec![ConnectionData::Pin(0), ConnectionData::Roller(2, 90.0)],
+            force: vec![
+                Force {
+                    p1: 1,
+                    p2: Point::new(1.0, -1.0),
+                    mag: 10.0,
+                    case: 0,
+                },
+                Force {
+                    p1: 3,
+                    p2: Point::new(2.0, 1.0),
+                    mag: 5.0,
+                    case: 0,
+                },
+            ],
+            settlements: vec![Settlement {
+                node: 2,
+                dx: 0.0,
+                dy: -0.01,
+            }],
+            ..Default::default()
+        };
+        let before = model.clone();
+        let removed = model.remove(&[Entity::Node(1), Entity::Member(3), Entity::Support(0)]);
+
+        assert_eq!(model.points.len(), 3);
+        let members: Vec<_> = model.edges.iter().map(|m| (m.p1, m.p2)).collect();
+        assert_eq!(members, [(1, 2)]);
+        assert!(matches!(
+            model.connections.as_slice(),
+            [ConnectionData::Roller(1, _)]
+        ));
+        assert_eq!(model.force.len(), 1);
+        assert_eq!(model.force[0].p1, 2);
+        assert_eq!(model.settlements[0].node, 1);
+
+        model.restore(&removed);
+        assert_eq!(model, before);
+    }
+}
@@ -1,2 +1,7 @@
+#[allow(clippy::module_inception)]
 pub mod physics;
+pub mod result;
+pub mod stability;
 pub use physics::*;
+pub use result::*;
+pub use stability::*;
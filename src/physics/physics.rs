@@ -1,166 +1,195 @@
-use std::f32;
-use std::ops::Deref;
-
-use crate::Connection;
 use crate::ConnectionData;
-use crate::Force;
+use crate::Library;
 use crate::Member;
-use crate::Truss;
-use egui::Pos2;
-use egui::emath::Numeric;
+use crate::Model;
+use crate::Point;
+use crate::TrussError;
+use crate::physics::result::*;
+use crate::physics::stability::{Determinacy, classify, equilibrium_matrix, reaction_count};
 use nalgebra::DMatrix;
 use nalgebra::Matrix1x4;
 use nalgebra::Matrix4x1;
 use nalgebra::linalg::FullPivLU;
-use nalgebra_sparse::CooMatrix;
-pub fn calculate_member_stress(truss: &mut Truss) -> DMatrix<f32> {
-    let size = 2 * truss.points.len();
-    let mut reactions = 0;
-    let mut matrix = DMatrix::<f32>::zeros(size, size);
-
-    let mut zeros = DMatrix::<f32>::zeros(size, 1);
-    print!("matrix, {:?}", matrix);
-    for node in &truss.points {
-        println!("Node: {:?}", node);
+use serde::{Deserialize, Serialize};
+/// Checks that every index refers to an existing node and that no member or
+/// force is degenerate.
+pub fn validate(truss: &Model) -> Result<(), TrussError> {
+    let n = truss.points.len();
+    let check = |kind, index, node: usize| {
+        if node < n {
+            Ok(())
+        } else {
+            Err(TrussError::DanglingIndex { kind, index, node })
+        }
+    };
+    for (idx, member) in truss.edges.iter().enumerate() {
+        check("member", idx, member.p1)?;
+        check("member", idx, member.p2)?;
+        if truss.points[member.p1].distance(truss.points[member.p2]) <= f32::EPSILON {
+            return Err(TrussError::ZeroLengthMember(idx));
+        }
+        if member.material >= truss.library.materials.len() {
+            return Err(TrussError::MissingProperty {
+                member: idx,
+                kind: "material",
+                index: member.material,
+            });
+        }
+        if member.section >= truss.library.sections.len() {
+            return Err(TrussError::MissingProperty {
+                member: idx,
+                kind: "section",
+                index: member.section,
+            });
+        }
     }
-    for member in &truss.edges {
-        println!("Member {:?}", member);
+    for (idx, connection) in truss.connections.iter().enumerate() {
+        match connection {
+            ConnectionData::Pin(node) => check("support", idx, *node)?,
+            ConnectionData::Roller(node, angle) => {
+                check("support", idx, *node)?;
+                if !angle.is_finite() {
+                    return Err(TrussError::Format(format!(
+                        "support {idx} has roller angle {angle}"
+                    )));
+                }
+            }
+        }
     }
-    // puts reactions at the node id for x and the node id +1 for y eqautions
-    // the second half(left to right) of the matrix should be reactions, whereas the first half
-    // should be the forces in all the members.
-    let mut halfsize = truss.edges.len();
-    println!("halfsize{}", halfsize);
-
-    for connection in &truss.connections {
-        halfsize += match connection {
-            ConnectionData::Pin(id) => {
-                reactions += 2;
-                matrix[(1, 2)] = 1.0;
-                // matrix[(id * 2, halfsize)] = 1.;
+    for (idx, settlement) in truss.settlements.iter().enumerate() {
+        check("settlement", idx, settlement.node)?;
+    }
+    for (idx, force) in truss.force.iter().enumerate() {
+        check("force", idx, force.p1)?;
+        if truss.points[force.p1].distance(force.p2) <= f32::EPSILON {
+            return Err(TrussError::ZeroLengthForce(idx));
+        }
+        if force.case >= truss.case_count() {
+            return Err(TrussError::MissingLoadCase {
+                kind: "force",
+                index: idx,
+                case: force.case,
+            });
+        }
+    }
+    for (idx, combination) in truss.combinations.iter().enumerate() {
+        for &(case, _) in &combination.factors {
+            if case >= truss.case_count() {
+                return Err(TrussError::MissingLoadCase {
+                    kind: "combination",
+                    index: idx,
+                    case,
+                });
+            }
+        }
+    }
+    Ok(())
+}
 
-                println!("put pin at {},{}", id, halfsize);
-                matrix[(id * 2 + 1, halfsize + 1)] = 1.;
+/// Solves a statically determinate truss by the method of joints. The
+/// equilibrium matrix is then square and nonsingular, so member forces and
+/// reactions follow from `A·[t; r] = -F` alone; member properties only scale
+/// the reported stress and strain.
+pub fn calculate_member_stress(truss: &Model) -> Result<StaticsResult, TrussError> {
+    let stability = classify(truss)?;
+    if truss.points.is_empty() {
+        return Err(TrussError::Unstable("the model has no nodes".to_string()));
+    }
+    match stability.determinacy {
+        Determinacy::Determinate => {}
+        Determinacy::Indeterminate { degree } => return Err(TrussError::Indeterminate(degree)),
+        Determinacy::Unstable { .. } => return Err(TrussError::Unstable(stability.to_string())),
+    }
+    let f = construct_force_matrix::<f64>(truss);
+    let solution = FullPivLU::new(equilibrium_matrix(truss))
+        .solve(&-&f)
+        .ok_or_else(|| TrussError::Unstable("equilibrium matrix is singular".to_string()))?;
 
-                println!("put pin at {},{}", id, halfsize + 1);
-                2
+    let members = truss
+        .edges
+        .iter()
+        .enumerate()
+        .map(|(idx, member)| {
+            let (length, _, _) = geometry::<f64>(member, &truss.points);
+            let (area, youngs) = properties::<f64>(member, &truss.library);
+            let force = solution[idx];
+            MemberResult {
+                member: idx,
+                p1: member.p1,
+                p2: member.p2,
+                length,
+                force,
+                stress: force / area,
+                strain: force / area / youngs,
             }
-            ConnectionData::Roller(id) => {
-                reactions += 1;
-                matrix[(id * 2 + 1, halfsize)] = 1.;
+        })
+        .collect();
 
-                1
+    // Reaction unknowns follow the members, in the order of the supports.
+    let mut reactions: Vec<Reaction> = Vec::new();
+    let mut col = truss.edges.len();
+    for connection in &truss.connections {
+        let (node, rx, ry) = match connection {
+            ConnectionData::Pin(node) => (*node, solution[col], solution[col + 1]),
+            ConnectionData::Roller(node, angle) => {
+                let (c, s) = unit_direction::<f64>(*angle);
+                (*node, c * solution[col], s * solution[col])
             }
         };
-        println!("halfsize count {halfsize}");
-    }
-    for force in &truss.force {
-        print!("force id{}", force.p1);
-        matrix[(force.p1, halfsize - 1)];
-    }
-    if size == truss.edges.len() + reactions {
-        println!("Solvable!");
-        let mut i = 0;
-        for member in &truss.edges {
-            let start = truss.points[member.p1];
-            let end = truss.points[member.p2];
-            let dx = end.x - start.x;
-            let dy = end.y - start.y;
-            let length = (dx * dx + dy * dy).sqrt();
-
-            let col = i; // member force column
-
-            let row_x_start = 2 * member.p1;
-            let row_y_start = 2 * member.p1 + 1;
-            let row_x_end = 2 * member.p2;
-            let row_y_end = 2 * member.p2 + 1;
-
-            i += 1;
-            // Start node
-            // println!("placing {row_x_start}, {col}");
-            //
-            // println!("placing {row_y_start}, {col}");
-            //
-            // println!("placing {row_x_end}, {col}");
-            //
-            // println!("placing {row_y_end}, {col}");
-            matrix[(row_x_start, col)] = dx / length;
-            matrix[(row_y_start, col)] = dy / length;
-            // End node (negative)
-            matrix[(row_x_end, col)] = -dx / length;
-            matrix[(row_y_end, col)] = -dy / length;
-        }
-        for force in &truss.force {
-            let start = truss.points[force.p1];
-            let end = force.p2;
-            let diff = start - end;
-            print!("diff{}", diff);
-            let anglex = diff.x / diff.length();
-            let angley = diff.y / diff.length();
-            println!("angle_x, {}", anglex);
-
-            println!("angle_y, {}", angley);
-            let id = force.p1;
-            zeros[(id * 2 + 1, 0)] = force.mag * angley;
-            zeros[(force.p1 * 2, 0)] = force.mag * anglex;
-        }
-        println!("forcing{}", zeros);
-        println!("matrix after placing coefficeints: {}", matrix);
-        let decomp = FullPivLU::new(matrix);
-
-        // let u = decomp.U();
-        //
-        // let tol = 1e-9;
-        // let mut singular = false;
-        //
-        // for i in 0..u.nrows().min(u.ncols()) {
-        //     if u[(i, i)].abs() < tol {
-        //         singular = true;
-        //         break;
-        //     }
-        // // }
-        // if singular {
-        //     panic!("matrix is singular and cannot be solved")
-        // } else {
-        // println!("matrix is not singular and is  invertable");
-        decomp.solve_mut(&mut zeros);
-        println!("sol? :{}", zeros);
-        // }
-    } else if truss.points.len() + reactions > size {
-        println!("overdefined")
-    } else if truss.points.len() + reactions < size {
-        print!(
-            "truss points + reactions{}",
-            (truss.points.len() + reactions)
-        );
-        print!("size{}", size);
-        let morereactions = size - reactions;
-        println!("need {} more reactions", morereactions);
-        println!("reactions: {}", reactions);
+        col += reaction_count(connection);
+        match reactions.iter_mut().find(|r| r.node == node) {
+            Some(reaction) => {
+                reaction.rx += rx;
+                reaction.ry += ry;
+            }
+            None => reactions.push(Reaction { node, rx, ry }),
+        }
     }
-    zeros
+    let equilibrium = equilibrium(truss, &f, &reactions);
+
+    Ok(StaticsResult {
+        members,
+        reactions,
+        equilibrium,
+    })
 }
+use nalgebra::RealField;
 use nalgebra::base::Matrix4;
+use nalgebra_sparse::CooMatrix;
 use nalgebra_sparse::CscMatrix;
-use nalgebra_sparse::CsrMatrix;
-use nalgebra_sparse::csc;
 use nalgebra_sparse::factorization::CscCholesky;
-use nalgebra_sparse_linalg::na_sparse::na::Matrix1;
-use nalgebra_sparse_linalg::na_sparse::na::MatrixXx1;
 
-fn construct_stiffness_matrix(member: &Member, points: &Vec<Pos2>) -> Matrix4<f32> {
-    let p1 = points[member.p1];
+/// Lifts a model value (stored as `f32` for drawing) into the solver's scalar.
+fn real<T: RealField>(x: f32) -> T {
+    nalgebra::convert(x as f64)
+}
 
+/// Length and direction cosines of a member, computed in `T`.
+fn geometry<T: RealField + Copy>(member: &Member, points: &[Point]) -> (T, T, T) {
+    let p1 = points[member.p1];
     let p2 = points[member.p2];
-    let deltay = p2.y - p1.y;
-    let deltax = p2.x - p1.x;
-    let theta = f32::atan2(deltay, deltax);
-    let c = f32::cos(theta);
-    let length = p1.distance(p2);
-    let A_s = 2.0;
-    let E = 69e9;
-
-    let s = f32::sin(theta);
+    let deltax = real::<T>(p2.x) - real(p1.x);
+    let deltay = real::<T>(p2.y) - real(p1.y);
+    let length = deltax.hypot(deltay);
+    (length, deltax / length, deltay / length)
+}
+
+/// Section area and Young's modulus of a member, in `T`.
+fn properties<T: RealField + Copy>(member: &Member, library: &Library) -> (T, T) {
+    (
+        nalgebra::convert(library.sections[member.section].area),
+        nalgebra::convert(library.materials[member.material].youngs),
+    )
+}
+
+fn construct_stiffness_matrix<T: RealField + Copy>(
+    member: &Member,
+    points: &[Point],
+    library: &Library,
+) -> Matrix4<T> {
+    let (length, c, s) = geometry::<T>(member, points);
+    let (area, youngs) = properties::<T>(member, library);
+
     let stiffness = Matrix4::new(
         c * c,
         c * s,
@@ -179,14 +208,14 @@ fn construct_stiffness_matrix(member: &Member, points: &Vec<Pos2>) -> Matrix4<f3
         c * s,
         s * s,
     );
-    stiffness * (A_s * E / length)
+    stiffness * (area * youngs / length)
 }
 
-fn construct_global_stiffness(truss: &Truss) -> CooMatrix<f32> {
+fn construct_global_stiffness<T: RealField + Copy>(truss: &Model) -> CooMatrix<T> {
     let n = truss.points.len();
-    let mut k_global = CooMatrix::<f32>::zeros(2 * n, 2 * n);
+    let mut k_global = CooMatrix::<T>::zeros(2 * n, 2 * n);
     for m in &truss.edges {
-        let k_local = construct_stiffness_matrix(m, &truss.points);
+        let k_local = construct_stiffness_matrix::<T>(m, &truss.points, &truss.library);
         let map = [2 * m.p1, 2 * m.p1 + 1, 2 * m.p2, 2 * m.p2 + 1];
 
         for i in 0..4 {
@@ -197,85 +226,921 @@ fn construct_global_stiffness(truss: &Truss) -> CooMatrix<f32> {
     }
     k_global
 }
-fn construct_force_matrix(truss: &Truss) -> DMatrix<f32> {
+/// Global load vector of every force, whatever its load case.
+fn construct_force_matrix<T: RealField + Copy>(truss: &Model) -> DMatrix<T> {
+    load_vector(truss, |_| T::one())
+}
+
+/// Global load vector with the forces of each load case scaled by
+/// `factor(case)`.
+fn load_vector<T: RealField + Copy>(truss: &Model, factor: impl Fn(usize) -> T) -> DMatrix<T> {
     let mut forces = DMatrix::zeros(2 * truss.points.len(), 1);
     for force in &truss.force {
         let start = truss.points[force.p1];
         let end = force.p2;
-        let diff = start - end;
-        print!("diff{}", diff);
-        let anglex = diff.x / diff.length();
-        let angley = diff.y / diff.length();
-        println!("angle_x, {}", anglex);
-
-        println!("angle_y, {}", angley);
-        let id = force.p1;
-        forces[(id * 2 + 1, 0)] = force.mag * angley;
-        forces[(force.p1 * 2, 0)] = force.mag * anglex;
-    }
-    print!("{}", forces);
+        let diffx = real::<T>(end.x) - real(start.x);
+        let diffy = real::<T>(end.y) - real(start.y);
+        let length = diffx.hypot(diffy);
+        let mag = real::<T>(force.mag) * factor(force.case);
+        forces[(force.p1 * 2, 0)] += mag * diffx / length;
+        forces[(force.p1 * 2 + 1, 0)] += mag * diffy / length;
+    }
     forces
 }
-fn dofpenalty(truss: &Truss, mut stiffness: CooMatrix<f32>, mut f: DMatrix<f32>) -> DMatrix<f32> {
-    let penalty = 1e8
-        * stiffness
-            .values()
-            .iter()
-            .fold(0.0_f32, |a, &b| a.max(b.abs()));
+
+/// Unit vector at `degrees` from the +x axis, exact on the axes so that
+/// horizontal and vertical rollers need no rounding.
+pub(crate) fn unit_direction<T: RealField + Copy>(degrees: f32) -> (T, T) {
+    let quarter = degrees / 90.0;
+    if quarter.fract() == 0.0 {
+        let (c, s) = match (quarter as i64).rem_euclid(4) {
+            0 => (1.0, 0.0),
+            1 => (0.0, 1.0),
+            2 => (-1.0, 0.0),
+            _ => (0.0, -1.0),
+        };
+        return (nalgebra::convert(c), nalgebra::convert(s));
+    }
+    let radians = real::<T>(degrees) * T::pi() / nalgebra::convert(180.0);
+    (radians.cos(), radians.sin())
+}
+
+/// Restrained direction of every node whose DOFs are rotated into a local
+/// frame: the first roller at a node that is not pinned. In that frame DOF
+/// `2i` runs along the rolling surface and `2i + 1` along the restraint.
+fn node_frames(truss: &Model) -> Vec<Option<f32>> {
+    let mut frames = vec![None; truss.points.len()];
+    for connection in &truss.connections {
+        if let ConnectionData::Roller(node, angle) = connection {
+            frames[*node] = frames[*node].or(Some(*angle));
+        }
+    }
+    for connection in &truss.connections {
+        if let ConnectionData::Pin(node) = connection {
+            frames[*node] = None;
+        }
+    }
+    frames
+}
+
+/// Restrained degrees of freedom, in the nodal frames, as (node, dof index)
+/// pairs. A second roller at a node in another direction fixes it fully.
+fn restrained_dofs(truss: &Model, frames: &[Option<f32>]) -> Vec<(usize, usize)> {
+    let mut dofs = Vec::new();
     for connection in &truss.connections {
         match connection {
             ConnectionData::Pin(idx) => {
-                let x = 2 * idx;
-                let y = 2 * idx + 1;
+                dofs.push((*idx, 2 * idx));
+                dofs.push((*idx, 2 * idx + 1));
+            }
+            ConnectionData::Roller(idx, angle) => {
+                if frames[*idx].is_some_and(|frame| (frame - angle).rem_euclid(180.0) != 0.0) {
+                    dofs.push((*idx, 2 * idx));
+                }
+                dofs.push((*idx, 2 * idx + 1));
+            }
+        }
+    }
+    dofs.sort_unstable();
+    dofs.dedup();
+    dofs
+}
+
+/// Columns of the rotation `u_global = R·u_local` that touch global DOF
+/// `dof`, as (local dof, coefficient) pairs.
+fn rotation<T: RealField + Copy>(frames: &[Option<f32>], dof: usize) -> [(usize, T); 2] {
+    let node = dof / 2;
+    match frames[node] {
+        None => [(dof, T::one()), (dof, T::zero())],
+        Some(angle) => {
+            // Surface direction t = (s, -c), restraint n = (c, s).
+            let (c, s) = unit_direction::<T>(angle);
+            if dof.is_multiple_of(2) {
+                [(2 * node, s), (2 * node + 1, c)]
+            } else {
+                [(2 * node, -c), (2 * node + 1, s)]
+            }
+        }
+    }
+}
+
+/// `Rᵀ·K·R`: the stiffness expressed in the nodal frames.
+fn rotate_stiffness<T: RealField + Copy>(
+    frames: &[Option<f32>],
+    stiffness: &CooMatrix<T>,
+) -> CooMatrix<T> {
+    let mut rotated = CooMatrix::zeros(stiffness.nrows(), stiffness.ncols());
+    for (row, col, &value) in stiffness.triplet_iter() {
+        for (i, a) in rotation::<T>(frames, row) {
+            for (j, b) in rotation::<T>(frames, col) {
+                if a != T::zero() && b != T::zero() {
+                    rotated.push(i, j, a * value * b);
+                }
+            }
+        }
+    }
+    rotated
+}
+
+/// `Rᵀ·v`: a global vector expressed in the nodal frames.
+fn to_local<T: RealField + Copy>(frames: &[Option<f32>], v: &DMatrix<T>) -> DMatrix<T> {
+    let mut local = DMatrix::zeros(v.nrows(), 1);
+    for dof in 0..v.nrows() {
+        for (i, a) in rotation::<T>(frames, dof) {
+            local[i] += a * v[dof];
+        }
+    }
+    local
+}
+
+/// `R·v`: a vector in the nodal frames expressed globally.
+fn to_global<T: RealField + Copy>(frames: &[Option<f32>], v: &DMatrix<T>) -> DMatrix<T> {
+    let mut global = DMatrix::zeros(v.nrows(), 1);
+    for dof in 0..v.nrows() {
+        for (i, a) in rotation::<T>(frames, dof) {
+            global[dof] += a * v[i];
+        }
+    }
+    global
+}
+
+/// Value of every degree of freedom fixed by a support, in the nodal frames:
+/// zero unless a settlement prescribes otherwise. Free entries are zero.
+fn prescribed_displacements<T: RealField + Copy>(
+    truss: &Model,
+    frames: &[Option<f32>],
+    restrained: &[(usize, usize)],
+) -> DMatrix<T> {
+    let mut settlements = DMatrix::zeros(2 * truss.points.len(), 1);
+    for settlement in &truss.settlements {
+        settlements[2 * settlement.node] = real(settlement.dx);
+        settlements[2 * settlement.node + 1] = real(settlement.dy);
+    }
+    let settlements = to_local(frames, &settlements);
+    let mut u = DMatrix::zeros(settlements.nrows(), 1);
+    for &(_, dof) in restrained {
+        u[dof] = settlements[dof];
+    }
+    u
+}
+
+/// The stiffness system with its supports imposed, factorized once so that
+/// any number of load vectors can be solved against it.
+enum Factorized<T: RealField> {
+    /// Restrained DOFs removed, solving `K_ff·u_f = F_f - K_fr·u_r`.
+    Elimination {
+        /// Row of each DOF in `K_ff`, or `None` if restrained.
+        free_index: Vec<Option<usize>>,
+        k_ff: CscMatrix<T>,
+        /// `K_fr·u_r`, the load the prescribed displacements put on the free
+        /// DOFs.
+        settlement_load: DMatrix<T>,
+        /// `None` when every DOF is restrained.
+        cholesky: Option<CscCholesky<T>>,
+    },
+    /// A large spring on every restrained diagonal.
+    Penalty {
+        penalty: T,
+        k: CscMatrix<T>,
+        cholesky: CscCholesky<T>,
+    },
+}
 
-                stiffness.push(x, x, penalty);
-                stiffness.push(y, y, penalty);
-                f[x] = 0.0;
-                f[y] = 0.0;
+impl<T: RealField + Copy> Factorized<T> {
+    /// Partitions K into free and restrained DOFs and factors `K_ff`.
+    fn eliminate(
+        stiffness: &CooMatrix<T>,
+        restrained: &[(usize, usize)],
+        prescribed: &DMatrix<T>,
+    ) -> Result<Self, TrussError> {
+        let mut free_index = vec![Some(0); stiffness.nrows()];
+        for &(_, dof) in restrained {
+            free_index[dof] = None;
+        }
+        let mut free = 0;
+        for idx in free_index.iter_mut().flatten() {
+            *idx = free;
+            free += 1;
+        }
+
+        let mut k_ff = CooMatrix::<T>::zeros(free, free);
+        let mut settlement_load = DMatrix::<T>::zeros(free, 1);
+        for (row, col, &value) in stiffness.triplet_iter() {
+            match (free_index[row], free_index[col]) {
+                (Some(i), Some(j)) => k_ff.push(i, j, value),
+                (Some(i), None) => settlement_load[i] += value * prescribed[col],
+                _ => {}
             }
-            ConnectionData::Roller(idx) => {
-                let y = 2 * idx + 1;
-                stiffness.push(y, y, penalty);
-                f[y] = 0.0;
+        }
+        let k_ff = CscMatrix::from(&k_ff);
+        let cholesky = if free == 0 {
+            None
+        } else {
+            Some(
+                CscCholesky::factor(&k_ff)
+                    .map_err(|err| TrussError::Factorization(format!("{err:?}")))?,
+            )
+        };
+        Ok(Factorized::Elimination {
+            free_index,
+            k_ff,
+            settlement_load,
+            cholesky,
+        })
+    }
+
+    /// Adds a spring `1e8` times stiffer than the stiffest entry on every
+    /// restrained diagonal and factors the whole system.
+    fn penalty(
+        stiffness: &CooMatrix<T>,
+        restrained: &[(usize, usize)],
+    ) -> Result<Self, TrussError> {
+        let mut stiffness = stiffness.clone();
+        let penalty = nalgebra::convert::<f64, T>(1e8)
+            * stiffness
+                .values()
+                .iter()
+                .fold(T::zero(), |a, &b| a.max(b.abs()));
+        for &(_, dof) in restrained {
+            stiffness.push(dof, dof, penalty);
+        }
+        let k = CscMatrix::from(&stiffness);
+        let cholesky =
+            CscCholesky::factor(&k).map_err(|err| TrussError::Factorization(format!("{err:?}")))?;
+        Ok(Factorized::Penalty {
+            penalty,
+            k,
+            cholesky,
+        })
+    }
+
+    /// Penalty added on restrained diagonals; zero for elimination.
+    fn penalty_value(&self) -> T {
+        match self {
+            Factorized::Elimination { .. } => T::zero(),
+            Factorized::Penalty { penalty, .. } => *penalty,
+        }
+    }
+
+    /// Displacements in the nodal frames under load `f`, and the residual of
+    /// the system solved.
+    fn solve(
+        &self,
+        f: &DMatrix<T>,
+        restrained: &[(usize, usize)],
+        prescribed: &DMatrix<T>,
+    ) -> (DMatrix<T>, T) {
+        match self {
+            Factorized::Elimination {
+                free_index,
+                k_ff,
+                settlement_load,
+                cholesky,
+            } => {
+                let mut u = prescribed.clone();
+                let Some(cholesky) = cholesky else {
+                    return (u, T::zero());
+                };
+                let mut rhs = -settlement_load;
+                for (dof, idx) in free_index.iter().enumerate() {
+                    if let Some(i) = idx {
+                        rhs[*i] += f[dof];
+                    }
+                }
+                let u_f = cholesky.solve(&rhs);
+                let residual = (k_ff * &u_f - &rhs).amax();
+                for (dof, idx) in free_index.iter().enumerate() {
+                    if let Some(i) = idx {
+                        u[dof] = u_f[*i];
+                    }
+                }
+                (u, residual)
+            }
+            Factorized::Penalty {
+                penalty,
+                k,
+                cholesky,
+            } => {
+                let mut f = f.clone();
+                for &(_, dof) in restrained {
+                    f[dof] = *penalty * prescribed[dof];
+                }
+                let u = cholesky.solve(&f);
+                let residual = (k * &u - &f).amax();
+                (u, residual)
             }
         }
     }
-    let k_global: CscMatrix<f32> = CscMatrix::from(&stiffness);
-    println!("{:?}", k_global);
-    let den = DMatrix::from(&k_global);
-    // let eig = den.symetriceigen();
-    let cscC = CscCholesky::factor(&k_global).unwrap();
+}
 
-    let fa = cscC.solve(&f);
-    print!("stiffness{}", fa);
-    fa
+/// How restrained degrees of freedom are imposed on the stiffness system.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SolverMethod {
+    /// Remove restrained DOFs from the system and solve the free partition.
+    #[default]
+    Elimination,
+    /// Add a large spring on every restrained diagonal.
+    Penalty,
 }
-pub fn solve_stiff(truss: &mut Truss) {
-    let k_global = construct_global_stiffness(truss);
-    let f = construct_force_matrix(truss);
-    let awns = dofpenalty(truss, k_global, f);
-    for member in &truss.edges {
-        let points = &truss.points;
-        let p1 = points[member.p1];
-
-        let p2 = points[member.p2];
-        let deltay = p2.y - p1.y;
-        let deltax = p2.x - p1.x;
-        let theta = f32::atan2(deltay, deltax);
-        let c = f32::cos(theta);
-        let length = p1.distance(p2);
-        let A_s = 20.0;
-        let E = 69e9;
-
-        let s = f32::sin(theta);
-        let transform = Matrix1x4::new(-c, -s, c, s);
-        let disp = Matrix4x1::new(
-            awns[2 * member.p1],
-            awns[2 * member.p1 + 1],
-            awns[2 * member.p2],
-            awns[2 * member.p2 + 1],
+
+/// Everything about a truss that does not depend on its loads, with the
+/// stiffness factorized.
+struct System<T: RealField> {
+    k_global: CscMatrix<T>,
+    frames: Vec<Option<f32>>,
+    restrained: Vec<(usize, usize)>,
+    prescribed: DMatrix<T>,
+    factorized: Factorized<T>,
+}
+
+impl<T: RealField + Copy> System<T> {
+    fn new(truss: &Model) -> Result<Self, TrussError> {
+        let stability = classify(truss)?;
+        if truss.points.is_empty() {
+            return Err(TrussError::Unstable("the model has no nodes".to_string()));
+        }
+        if !stability.is_stable() {
+            return Err(TrussError::Unstable(stability.to_string()));
+        }
+        let k_global = construct_global_stiffness::<T>(truss);
+
+        // Inclined rollers restrain one DOF of a rotated nodal frame.
+        let frames = node_frames(truss);
+        let restrained = restrained_dofs(truss, &frames);
+        let prescribed = prescribed_displacements::<T>(truss, &frames, &restrained);
+        let k_local = rotate_stiffness(&frames, &k_global);
+        let factorized = match truss.solver {
+            SolverMethod::Elimination => Factorized::eliminate(&k_local, &restrained, &prescribed)?,
+            SolverMethod::Penalty => Factorized::penalty(&k_local, &restrained)?,
+        };
+        Ok(System {
+            k_global: CscMatrix::from(&k_global),
+            frames,
+            restrained,
+            prescribed,
+            factorized,
+        })
+    }
+
+    /// Displacements, member forces and reactions under the global load
+    /// vector `f`.
+    fn solve(&self, truss: &Model, f: &DMatrix<T>) -> AnalysisResult<T> {
+        let f_local = to_local(&self.frames, f);
+        let (u_local, residual) =
+            self.factorized
+                .solve(&f_local, &self.restrained, &self.prescribed);
+        let awns = to_global(&self.frames, &u_local);
+
+        let displacements: Vec<NodeDisplacement<T>> = (0..truss.points.len())
+            .map(|node| NodeDisplacement {
+                node,
+                dx: awns[2 * node],
+                dy: awns[2 * node + 1],
+            })
+            .collect();
+
+        let mut members = Vec::with_capacity(truss.edges.len());
+        for (idx, member) in truss.edges.iter().enumerate() {
+            let (length, c, s) = geometry::<T>(member, &truss.points);
+            let (area, youngs) = properties::<T>(member, &truss.library);
+
+            let transform = Matrix1x4::new(-c, -s, c, s);
+            let disp = Matrix4x1::new(
+                awns[2 * member.p1],
+                awns[2 * member.p1 + 1],
+                awns[2 * member.p2],
+                awns[2 * member.p2 + 1],
+            );
+            let force = (transform * disp)[0] * (area * youngs / length);
+            let stress = force / area;
+            members.push(MemberResult {
+                member: idx,
+                p1: member.p1,
+                p2: member.p2,
+                length,
+                force,
+                stress,
+                strain: stress / youngs,
+            });
+        }
+
+        // Reactions are recovered from the unmodified stiffness: R = K·u - F.
+        // Both global components are reported; along a free direction the
+        // residual is zero.
+        let support_forces = &self.k_global * &awns - f;
+        let mut reactions: Vec<Reaction<T>> = Vec::new();
+        for connection in &truss.connections {
+            let node = connection.node();
+            if reactions.iter().all(|r| r.node != node) {
+                reactions.push(Reaction {
+                    node,
+                    rx: support_forces[2 * node],
+                    ry: support_forces[2 * node + 1],
+                });
+            }
+        }
+        let equilibrium = equilibrium(truss, f, &reactions);
+
+        let max_displacement = displacements
+            .iter()
+            .fold(T::zero(), |a, d| a.max(d.dx.hypot(d.dy)));
+
+        AnalysisResult {
+            displacements,
+            members,
+            reactions,
+            diagnostics: SolverDiagnostics {
+                method: truss.solver,
+                dofs: 2 * truss.points.len(),
+                restrained_dofs: self.restrained.len(),
+                penalty: self.factorized.penalty_value(),
+                residual,
+                max_displacement,
+                equilibrium,
+            },
+        }
+    }
+}
+
+/// Solves the truss by the direct stiffness method in double precision.
+/// Every load case is applied at once, unfactored.
+pub fn solve_stiff(truss: &Model) -> Result<AnalysisResult, TrussError> {
+    solve_stiff_with::<f64>(truss)
+}
+
+/// Solves the truss by the direct stiffness method in the scalar type `T`.
+pub fn solve_stiff_with<T: RealField + Copy>(
+    truss: &Model,
+) -> Result<AnalysisResult<T>, TrussError> {
+    let system = System::<T>::new(truss)?;
+    Ok(system.solve(truss, &construct_force_matrix(truss)))
+}
+
+/// Solves every load case and combination of the truss against a single
+/// factorization of its stiffness. Settlements are imposed, unfactored, in
+/// each of them.
+pub fn solve_load_cases(truss: &Model) -> Result<LoadCaseResults, TrussError> {
+    let system = System::<f64>::new(truss)?;
+    let cases: Vec<NamedResult> = (0..truss.case_count())
+        .map(|case| NamedResult {
+            name: truss.case_name(case).to_string(),
+            result: system.solve(
+                truss,
+                &load_vector(truss, |c| if c == case { 1.0 } else { 0.0 }),
+            ),
+        })
+        .collect();
+    let combinations: Vec<NamedResult> = (truss.combinations.iter())
+        .map(|combination| {
+            let factor = |case: usize| {
+                (combination.factors.iter())
+                    .filter(|(c, _)| *c == case)
+                    .map(|(_, factor)| factor)
+                    .sum()
+            };
+            NamedResult {
+                name: combination.name.clone(),
+                result: system.solve(truss, &load_vector(truss, factor)),
+            }
+        })
+        .collect();
+    let envelope = if combinations.is_empty() {
+        envelope(&cases)
+    } else {
+        envelope(&combinations)
+    };
+    Ok(LoadCaseResults {
+        cases,
+        combinations,
+        envelope,
+    })
+}
+
+/// Largest and smallest force of every member over `results`.
+fn envelope(results: &[NamedResult]) -> Vec<MemberEnvelope> {
+    let Some(first) = results.first() else {
+        return Vec::new();
+    };
+    (first.result.members.iter())
+        .map(|member| {
+            let mut envelope = MemberEnvelope {
+                member: member.member,
+                max_force: member.force,
+                max_by: first.name.clone(),
+                min_force: member.force,
+                min_by: first.name.clone(),
+            };
+            for named in &results[1..] {
+                let force = named.result.members[member.member].force;
+                if force > envelope.max_force {
+                    envelope.max_force = force;
+                    envelope.max_by = named.name.clone();
+                }
+                if force < envelope.min_force {
+                    envelope.min_force = force;
+                    envelope.min_by = named.name.clone();
+                }
+            }
+            envelope
+        })
+        .collect()
+}
+
+/// Sums applied loads and reactions over the whole structure. Moments are
+/// taken about the origin, counter-clockwise positive.
+fn equilibrium<T: RealField + Copy>(
+    truss: &Model,
+    f: &DMatrix<T>,
+    reactions: &[Reaction<T>],
+) -> Equilibrium<T> {
+    let mut sum = Equilibrium {
+        fx: T::zero(),
+        fy: T::zero(),
+        moment: T::zero(),
+    };
+    let mut add = |node: usize, fx: T, fy: T| {
+        let p = truss.points[node];
+        sum.fx += fx;
+        sum.fy += fy;
+        sum.moment += real::<T>(p.x) * fy - real::<T>(p.y) * fx;
+    };
+    for node in 0..truss.points.len() {
+        add(node, f[2 * node], f[2 * node + 1]);
+    }
+    for reaction in reactions {
+        add(reaction.node, reaction.rx, reaction.ry);
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Combination;
+    use crate::Edit;
+    use crate::Force;
+    use crate::Section;
+    use crate::Settlement;
+
+    fn triangle() -> Model {
+        Model {
+            points: vec![
+                Point::new(0.0, 0.0),
+                Point::new(4.0, 0.0),
+                Point::new(0.0, 3.0),
+            ],
+            edges: vec![Member::new(2, 0), Member::new(0, 1), Member::new(1, 2)],
+            connections: vec![ConnectionData::Pin(0), ConnectionData::Roller(1, 90.0)],
+            force: vec![Force {
+                p1: 2,
+                p2: Point::new(1.0, 3.0),
+                mag: 1000.0,
+                case: 0,
+            }],
+            ..Default::default()
+        }
+    }
+
+    /// The triangle with a second, downward load at the top in a live case,
+    /// and a 1.2D + 1.6L combination.
+    fn two_cases() -> Model {
+        let mut truss = triangle();
+        let dead = truss.set_case("dead");
+        let live = truss.set_case("live");
+        truss.force.push(Force {
+            p1: 2,
+            p2: Point::new(0.0, 2.0),
+            mag: 500.0,
+            case: live,
+        });
+        truss.set_combination(Combination {
+            name: "ULS".to_string(),
+            factors: vec![(dead, 1.2), (live, 1.6)],
+        });
+        truss
+    }
+
+    #[test]
+    fn solve_stiff_reports_every_node_member_and_support() {
+        let result = solve_stiff(&triangle()).unwrap();
+
+        assert_eq!(result.displacements.len(), 3);
+        assert_eq!(result.members.len(), 3);
+        assert_eq!(result.reactions.len(), 2);
+        assert_eq!(result.diagnostics.dofs, 6);
+        assert_eq!(result.diagnostics.restrained_dofs, 3);
+        assert!(result.reaction(0).is_some() && result.reaction(1).is_some());
+        assert_eq!(result.member(2).map(|m| (m.p1, m.p2)), Some((1, 2)));
+    }
+
+    #[test]
+    fn reactions_balance_the_applied_load() {
+        let truss = triangle();
+        let result = solve_stiff(&truss).unwrap();
+        let load = construct_force_matrix::<f64>(&truss);
+        let pin = result.reaction(0).unwrap();
+        let roller = result.reaction(1).unwrap();
+
+        assert!(roller.rx.abs() < 1e-6);
+        assert!((pin.rx + load[4]).abs() < 1e-6);
+        assert!((pin.ry + roller.ry + load[5]).abs() < 1e-6);
+        assert!(result.diagnostics.equilibrium.max_abs() < 1e-6);
+    }
+
+    #[test]
+    fn loads_point_from_the_node_toward_p2() {
+        let truss = triangle();
+        let load = construct_force_matrix::<f64>(&truss);
+        assert_eq!((load[4], load[5]), (1000.0, 0.0));
+
+        let result = solve_stiff(&truss).unwrap();
+        assert!((result.reaction(0).unwrap().rx + 1000.0).abs() < 1e-6);
+        assert!(result.displacements[2].dx > 0.0);
+    }
+
+    #[test]
+    fn moving_a_loaded_node_keeps_its_load() {
+        let mut truss = triangle();
+        truss.force[0].p2 = Point::new(0.0, 2.0);
+        let load = construct_force_matrix::<f64>(&truss);
+        let pin = solve_stiff(&truss).unwrap().reaction(0).unwrap().ry;
+
+        let edit = Edit::MoveNodes(vec![(2, truss.points[2], Point::new(0.0, 1.0))]);
+        edit.apply(&mut truss);
+        assert_eq!(construct_force_matrix::<f64>(&truss), load);
+        let moved = solve_stiff(&truss).unwrap().reaction(0).unwrap().ry;
+        assert!((moved - pin).abs() < 1e-6);
+
+        edit.revert(&mut truss);
+        assert_eq!(truss.force[0].p2, Point::new(0.0, 2.0));
+    }
+
+    #[test]
+    fn solve_stiff_keeps_supports_in_place() {
+        let result = solve_stiff(&triangle()).unwrap();
+        let top = &result.displacements[2];
+
+        assert!(top.dx.abs() > 0.0);
+        assert!(result.displacements[0].dx.abs() < top.dx.abs() * 1e-3);
+        assert!(result.displacements[0].dy.abs() < top.dx.abs() * 1e-3);
+        assert!(result.displacements[1].dy.abs() < top.dx.abs() * 1e-3);
+    }
+
+    #[test]
+    fn largest_displacement_is_at_the_free_node() {
+        let result = solve_stiff(&triangle()).unwrap();
+        let largest = result.largest_displacement().unwrap();
+
+        assert_eq!(largest.node, 2);
+        assert_eq!(
+            largest.dx.hypot(largest.dy),
+            result.diagnostics.max_displacement
+        );
+    }
+
+    #[test]
+    fn elimination_matches_penalty() {
+        let mut truss = triangle();
+        let exact = solve_stiff(&truss).unwrap();
+        truss.solver = SolverMethod::Penalty;
+        let penalty = solve_stiff(&truss).unwrap();
+
+        assert_eq!(exact.displacements[0].dx, 0.0);
+        assert_eq!(exact.displacements[1].dy, 0.0);
+        for (a, b) in exact.displacements.iter().zip(&penalty.displacements) {
+            let scale = exact.diagnostics.max_displacement;
+            assert!((a.dx - b.dx).abs() < 1e-3 * scale);
+            assert!((a.dy - b.dy).abs() < 1e-3 * scale);
+        }
+    }
+
+    #[test]
+    fn member_forces_balance_reactions() {
+        // Equilibrium of the pin node ties member forces to the reactions, so
+        // the two must agree whatever the member properties are.
+        let mut truss = triangle();
+        let steel = truss.library.material_index("steel").unwrap();
+        let heavy = truss.library.set_section(Section {
+            name: "heavy".to_string(),
+            area: 10.0,
+        });
+        truss.edges[0].material = steel;
+        truss.edges[1].section = heavy;
+        let result = solve_stiff(&truss).unwrap();
+
+        let pin = result.reaction(0).unwrap();
+        let vertical = &result.members[0];
+        let bottom = &result.members[1];
+        assert!((pin.rx + bottom.force).abs() < 1e-6);
+        assert!((pin.ry + vertical.force).abs() < 1e-6);
+        assert!((bottom.stress * 10.0 - bottom.force).abs() < 1e-6);
+        assert!((vertical.strain * 200e9 - vertical.stress).abs() < 1e-6);
+    }
+
+    #[test]
+    fn single_precision_agrees_with_double() {
+        let truss = triangle();
+        let single = solve_stiff_with::<f32>(&truss).unwrap();
+        let double = solve_stiff(&truss).unwrap();
+
+        for (a, b) in single.members.iter().zip(&double.members) {
+            assert!((f64::from(a.force) - b.force).abs() < 1e-3 * b.force.abs().max(1.0));
+        }
+    }
+
+    #[test]
+    fn settlement_is_imposed_exactly() {
+        let mut truss = triangle();
+        truss.force.clear();
+        truss.settlements.push(Settlement {
+            node: 1,
+            dx: 0.0,
+            dy: -0.01,
+        });
+        let result = solve_stiff(&truss).unwrap();
+
+        assert_eq!(result.displacements[1].dy, f64::from(-0.01_f32));
+        // A determinate truss follows a support settlement as a rigid body.
+        for member in &result.members {
+            assert!(member.strain.abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn inclined_roller_reacts_along_its_direction() {
+        let mut truss = triangle();
+        truss.connections[1] = ConnectionData::Roller(1, 60.0);
+        let result = solve_stiff(&truss).unwrap();
+
+        let (c, s) = unit_direction::<f64>(60.0);
+        let roller = result.reaction(1).unwrap();
+        let moved = &result.displacements[1];
+        // No reaction along the surface, no movement into it.
+        assert!((roller.rx * s - roller.ry * c).abs() < 1e-6);
+        assert!(roller.rx.hypot(roller.ry) > 1.0);
+        assert!((moved.dx * c + moved.dy * s).abs() < 1e-12);
+        assert!(result.diagnostics.equilibrium.max_abs() < 1e-6);
+
+        truss.solver = SolverMethod::Penalty;
+        let penalty = solve_stiff(&truss).unwrap();
+        assert!((penalty.reaction(1).unwrap().ry - roller.ry).abs() < 1e-3 * roller.ry.abs());
+    }
+
+    #[test]
+    fn horizontal_roller_restrains_x() {
+        let mut truss = triangle();
+        truss.connections[1] = ConnectionData::Roller(2, 0.0);
+        let result = solve_stiff(&truss).unwrap();
+
+        // The roller sits on the load's line of action and takes all of it.
+        let load = construct_force_matrix::<f64>(&truss);
+        let roller = result.reaction(2).unwrap();
+        assert_eq!(result.displacements[2].dx, 0.0);
+        assert!((roller.rx + load[4]).abs() < 1e-6);
+        assert!(roller.ry.abs() < 1e-6);
+    }
+
+    #[test]
+    fn joints_match_stiffness_on_a_determinate_truss() {
+        let mut truss = triangle();
+        truss.connections[1] = ConnectionData::Roller(1, 60.0);
+        let joints = calculate_member_stress(&truss).unwrap();
+        let stiff = solve_stiff(&truss).unwrap();
+
+        for (a, b) in joints.members.iter().zip(&stiff.members) {
+            assert_eq!(a.member, b.member);
+            assert!((a.force - b.force).abs() < 1e-6 * b.force.abs().max(1.0));
+            assert!((a.strain - b.strain).abs() < 1e-12);
+        }
+        for (a, b) in joints.reactions.iter().zip(&stiff.reactions) {
+            assert_eq!(a.node, b.node);
+            assert!((a.rx - b.rx).abs() < 1e-6 && (a.ry - b.ry).abs() < 1e-6);
+        }
+        assert!(joints.equilibrium.max_abs() < 1e-9);
+    }
+
+    #[test]
+    fn joints_follow_the_load_direction() {
+        // 1000 to the right at the top: the pin holds it back and the
+        // overturning moment pulls the pin down and the roller up.
+        let joints = calculate_member_stress(&triangle()).unwrap();
+        let pin = joints.reaction(0).unwrap();
+        let roller = joints.reaction(1).unwrap();
+
+        assert!((pin.rx + 1000.0).abs() < 1e-9);
+        assert!((pin.ry + 750.0).abs() < 1e-9);
+        assert!((roller.ry - 750.0).abs() < 1e-9);
+        assert!(roller.rx.abs() < 1e-9);
+        // The bottom chord carries the pin's horizontal reaction in tension.
+        assert!((joints.member(1).unwrap().force - 1000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn joints_reject_indeterminate_trusses() {
+        let mut truss = triangle();
+        truss.connections[1] = ConnectionData::Pin(1);
+        assert_eq!(
+            calculate_member_stress(&truss).unwrap_err(),
+            TrussError::Indeterminate(1)
+        );
+        assert!(solve_stiff(&truss).is_ok());
+    }
+
+    #[test]
+    fn degenerate_models_are_rejected() {
+        let mut truss = triangle();
+        truss.edges.push(Member::new(1, 7));
+        assert_eq!(
+            solve_stiff(&truss).unwrap_err(),
+            TrussError::DanglingIndex {
+                kind: "member",
+                index: 3,
+                node: 7
+            }
+        );
+
+        let mut truss = triangle();
+        truss.points[1] = truss.points[0];
+        assert_eq!(
+            solve_stiff(&truss).unwrap_err(),
+            TrussError::ZeroLengthMember(1)
+        );
+
+        let mut truss = triangle();
+        truss.force[0].p2 = truss.points[2];
+        assert_eq!(
+            solve_stiff(&truss).unwrap_err(),
+            TrussError::ZeroLengthForce(0)
+        );
+
+        let mut truss = triangle();
+        truss.edges[0].section = 3;
+        assert_eq!(
+            solve_stiff(&truss).unwrap_err(),
+            TrussError::MissingProperty {
+                member: 0,
+                kind: "section",
+                index: 3
+            }
+        );
+
+        let mut truss = triangle();
+        truss.connections[1] = ConnectionData::Roller(1, f32::NAN);
+        assert!(matches!(solve_stiff(&truss), Err(TrussError::Format(_))));
+        assert!(matches!(classify(&truss), Err(TrussError::Format(_))));
+
+        let mut truss = triangle();
+        truss.connections.clear();
+        assert!(matches!(solve_stiff(&truss), Err(TrussError::Unstable(_))));
+    }
+
+    #[test]
+    fn combinations_scale_and_add_their_cases() {
+        let truss = two_cases();
+        let results = solve_load_cases(&truss).unwrap();
+        assert_eq!(results.cases.len(), 2);
+        assert_eq!(results.cases[1].name, "live");
+
+        let dead = &results.cases[0].result;
+        let live = &results.cases[1].result;
+        let uls = results.named("ULS").unwrap();
+        for idx in 0..truss.edges.len() {
+            let expected = 1.2 * dead.members[idx].force + 1.6 * live.members[idx].force;
+            assert!((uls.members[idx].force - expected).abs() < 1e-6 * expected.abs().max(1.0));
+        }
+        // All loads at once is the sum of the cases.
+        let all = solve_stiff(&truss).unwrap();
+        let sum = dead.members[0].force + live.members[0].force;
+        assert!((all.members[0].force - sum).abs() < 1e-6 * sum.abs());
+    }
+
+    #[test]
+    fn envelope_spans_the_combinations() {
+        let mut truss = two_cases();
+        truss.set_combination(Combination {
+            name: "uplift".to_string(),
+            factors: vec![(0, 0.9), (1, -1.0)],
+        });
+        let results = solve_load_cases(&truss).unwrap();
+
+        for envelope in &results.envelope {
+            let forces: Vec<f64> = (results.combinations.iter())
+                .map(|c| c.result.members[envelope.member].force)
+                .collect();
+            let max = forces.iter().copied().fold(f64::MIN, f64::max);
+            let min = forces.iter().copied().fold(f64::MAX, f64::min);
+            assert_eq!((envelope.max_force, envelope.min_force), (max, min));
+            assert!(["ULS", "uplift"].contains(&envelope.max_by.as_str()));
+        }
+
+        truss.combinations[0].factors.push((2, 1.0));
+        assert_eq!(
+            solve_load_cases(&truss).unwrap_err(),
+            TrussError::MissingLoadCase {
+                kind: "combination",
+                index: 0,
+                case: 2
+            }
         );
-        let forcesvec = transform * disp * (A_s * E / length);
-        print!("forces member {}-{}, {}", member.p1, member.p2, forcesvec);
     }
 }
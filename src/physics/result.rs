@@ -0,0 +1,148 @@
+use crate::physics::SolverMethod;
+use nalgebra::RealField;
+use serde::Serialize;
+
+/// Displacement of a single node, in the same length units as `Truss::points`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NodeDisplacement<T = f64> {
+    pub node: usize,
+    pub dx: T,
+    pub dy: T,
+}
+
+/// Axial response of a member. Positive force and stress are tension.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MemberResult<T = f64> {
+    pub member: usize,
+    pub p1: usize,
+    pub p2: usize,
+    pub length: T,
+    pub force: T,
+    pub stress: T,
+    pub strain: T,
+}
+
+/// Force exerted by a support on the structure at `node`.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Reaction<T = f64> {
+    pub node: usize,
+    pub rx: T,
+    pub ry: T,
+}
+
+/// Net force and moment of loads plus reactions; all zero when in equilibrium.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Equilibrium<T = f64> {
+    pub fx: T,
+    pub fy: T,
+    pub moment: T,
+}
+
+impl<T: RealField + Copy> Equilibrium<T> {
+    pub fn max_abs(&self) -> T {
+        self.fx.abs().max(self.fy.abs()).max(self.moment.abs())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SolverDiagnostics<T = f64> {
+    pub method: SolverMethod,
+    /// Total degrees of freedom, two per node.
+    pub dofs: usize,
+    /// Degrees of freedom held by supports.
+    pub restrained_dofs: usize,
+    /// Stiffness added on restrained diagonals; zero for elimination.
+    pub penalty: T,
+    /// Infinity norm of `K·u - f` for the system that was solved.
+    pub residual: T,
+    /// Largest nodal displacement magnitude.
+    pub max_displacement: T,
+    /// Global equilibrium check of loads against the recovered reactions.
+    pub equilibrium: Equilibrium<T>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AnalysisResult<T = f64> {
+    pub displacements: Vec<NodeDisplacement<T>>,
+    pub members: Vec<MemberResult<T>>,
+    pub reactions: Vec<Reaction<T>>,
+    pub diagnostics: SolverDiagnostics<T>,
+}
+
+impl<T> AnalysisResult<T> {
+    pub fn member(&self, idx: usize) -> Option<&MemberResult<T>> {
+        self.members.iter().find(|m| m.member == idx)
+    }
+
+    pub fn reaction(&self, node: usize) -> Option<&Reaction<T>> {
+        self.reactions.iter().find(|r| r.node == node)
+    }
+}
+
+impl<T: RealField + Copy> AnalysisResult<T> {
+    /// The node that moves furthest, whose displacement is
+    /// `diagnostics.max_displacement`.
+    pub fn largest_displacement(&self) -> Option<&NodeDisplacement<T>> {
+        self.displacements.iter().max_by(|a, b| {
+            let (a, b) = (a.dx.hypot(a.dy), b.dx.hypot(b.dy));
+            a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal)
+        })
+    }
+}
+
+/// Results of one load case or combination.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NamedResult<T = f64> {
+    pub name: String,
+    pub result: AnalysisResult<T>,
+}
+
+/// Largest and smallest axial force a member sees, and the case or
+/// combination each comes from.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct MemberEnvelope<T = f64> {
+    pub member: usize,
+    pub max_force: T,
+    pub max_by: String,
+    pub min_force: T,
+    pub min_by: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LoadCaseResults<T = f64> {
+    pub cases: Vec<NamedResult<T>>,
+    pub combinations: Vec<NamedResult<T>>,
+    /// Over the combinations, or over the cases if there are none.
+    pub envelope: Vec<MemberEnvelope<T>>,
+}
+
+impl<T> LoadCaseResults<T> {
+    /// The case or combination called `name`. Combinations are searched
+    /// first.
+    pub fn named(&self, name: &str) -> Option<&AnalysisResult<T>> {
+        (self.combinations.iter())
+            .chain(&self.cases)
+            .find(|named| named.name == name)
+            .map(|named| &named.result)
+    }
+}
+
+/// Member forces and support reactions from joint equilibrium alone, for
+/// statically determinate trusses.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StaticsResult<T = f64> {
+    pub members: Vec<MemberResult<T>>,
+    pub reactions: Vec<Reaction<T>>,
+    /// Global equilibrium check of loads against the reactions.
+    pub equilibrium: Equilibrium<T>,
+}
+
+impl<T> StaticsResult<T> {
+    pub fn member(&self, idx: usize) -> Option<&MemberResult<T>> {
+        self.members.iter().find(|m| m.member == idx)
+    }
+
+    pub fn reaction(&self, node: usize) -> Option<&Reaction<T>> {
+        self.reactions.iter().find(|r| r.node == node)
+    }
+}
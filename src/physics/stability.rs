@@ -0,0 +1,272 @@
+use std::fmt;
+
+use crate::ConnectionData;
+use crate::Model;
+use crate::TrussError;
+use crate::physics::physics::{unit_direction, validate};
+use nalgebra::DMatrix;
+use nalgebra::linalg::SymmetricEigen;
+
+/// Eigenvalues of `A·Aᵀ` below this fraction of the largest count as zero.
+const RANK_TOLERANCE: f64 = 1e-10;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Determinacy {
+    Determinate,
+    /// Stable with `degree` more unknowns than equilibrium equations.
+    Indeterminate {
+        degree: usize,
+    },
+    /// `mechanisms` independent ways the structure can move without
+    /// straining a member; `nodes` are the nodes that move in them.
+    Unstable {
+        mechanisms: usize,
+        nodes: Vec<usize>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Stability {
+    pub members: usize,
+    pub reactions: usize,
+    /// Two equilibrium equations per node.
+    pub equations: usize,
+    /// Rank of the equilibrium matrix.
+    pub rank: usize,
+    pub determinacy: Determinacy,
+    /// Nodal displacement patterns (`2n` entries each) that no member or
+    /// support resists, normalised to a largest component of 1.
+    pub modes: Vec<Vec<f64>>,
+}
+
+impl Stability {
+    pub fn is_stable(&self) -> bool {
+        !matches!(self.determinacy, Determinacy::Unstable { .. })
+    }
+}
+
+impl fmt::Display for Stability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.determinacy {
+            Determinacy::Determinate => write!(f, "determinate"),
+            Determinacy::Indeterminate { degree } => {
+                write!(f, "indeterminate to degree {degree}")
+            }
+            Determinacy::Unstable { mechanisms, nodes } => {
+                write!(f, "unstable, {mechanisms} mechanism(s)")?;
+                if !nodes.is_empty() {
+                    let nodes: Vec<String> = nodes.iter().map(|n| n.to_string()).collect();
+                    write!(f, " moving nodes {}", nodes.join(", "))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Number of reaction components a support provides.
+pub fn reaction_count(connection: &ConnectionData) -> usize {
+    match connection {
+        ConnectionData::Pin(_) => 2,
+        ConnectionData::Roller(..) => 1,
+    }
+}
+
+/// Joint equilibrium matrix `A` with `A·[t; r] = -F`. Row `2i`/`2i + 1` is
+/// the x/y balance at node `i`; the first columns are member tensions, then
+/// one column per reaction component in the order of `truss.connections`.
+pub fn equilibrium_matrix(truss: &Model) -> DMatrix<f64> {
+    let reactions: usize = truss.connections.iter().map(reaction_count).sum();
+    let mut matrix = DMatrix::zeros(2 * truss.points.len(), truss.edges.len() + reactions);
+    for (col, member) in truss.edges.iter().enumerate() {
+        let start = truss.points[member.p1];
+        let end = truss.points[member.p2];
+        let dx = f64::from(end.x) - f64::from(start.x);
+        let dy = f64::from(end.y) - f64::from(start.y);
+        let length = dx.hypot(dy);
+        // Tension pulls each end toward the other.
+        matrix[(2 * member.p1, col)] = dx / length;
+        matrix[(2 * member.p1 + 1, col)] = dy / length;
+        matrix[(2 * member.p2, col)] = -dx / length;
+        matrix[(2 * member.p2 + 1, col)] = -dy / length;
+    }
+    let mut col = truss.edges.len();
+    for connection in &truss.connections {
+        match connection {
+            ConnectionData::Pin(node) => {
+                matrix[(2 * node, col)] = 1.0;
+                matrix[(2 * node + 1, col + 1)] = 1.0;
+            }
+            ConnectionData::Roller(node, angle) => {
+                let (c, s) = unit_direction::<f64>(*angle);
+                matrix[(2 * node, col)] = c;
+                matrix[(2 * node + 1, col)] = s;
+            }
+        }
+        col += reaction_count(connection);
+    }
+    matrix
+}
+
+/// Classifies the truss as determinate, indeterminate or unstable from the
+/// rank of its equilibrium matrix, which also catches geometric instability
+/// such as parallel or concurrent reactions when the counts alone balance.
+pub fn classify(truss: &Model) -> Result<Stability, TrussError> {
+    validate(truss)?;
+    let matrix = equilibrium_matrix(truss);
+    let equations = matrix.nrows();
+    let unknowns = matrix.ncols();
+
+    // Displacement patterns d with Aᵀ·d = 0 strain no member and move no
+    // support: they span the null space of A·Aᵀ. A model without nodes has
+    // none, and nalgebra cannot decompose an empty matrix.
+    let mut modes = Vec::new();
+    if equations > 0 {
+        let eigen = SymmetricEigen::new(&matrix * matrix.transpose());
+        let largest = eigen.eigenvalues.iter().fold(0.0_f64, |a, &b| a.max(b));
+        let tolerance = largest.max(1.0) * RANK_TOLERANCE;
+        for (idx, &value) in eigen.eigenvalues.iter().enumerate() {
+            if value <= tolerance {
+                let mode = eigen.eigenvectors.column(idx);
+                let scale = mode.amax();
+                modes.push(mode.iter().map(|v| v / scale).collect::<Vec<f64>>());
+            }
+        }
+    }
+    let rank = equations - modes.len();
+
+    let determinacy = if !modes.is_empty() {
+        let nodes: Vec<usize> = (0..truss.points.len())
+            .filter(|node| {
+                modes
+                    .iter()
+                    .any(|mode| mode[2 * node].hypot(mode[2 * node + 1]) > 1e-6)
+            })
+            .collect();
+        Determinacy::Unstable {
+            mechanisms: modes.len(),
+            nodes,
+        }
+    } else if unknowns > equations {
+        Determinacy::Indeterminate {
+            degree: unknowns - equations,
+        }
+    } else {
+        Determinacy::Determinate
+    };
+
+    Ok(Stability {
+        members: truss.edges.len(),
+        reactions: unknowns - truss.edges.len(),
+        equations,
+        rank,
+        determinacy,
+        modes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Member;
+    use crate::Point;
+
+    fn square() -> Model {
+        Model {
+            points: vec![
+                Point::new(0.0, 0.0),
+                Point::new(1.0, 0.0),
+                Point::new(1.0, 1.0),
+                Point::new(0.0, 1.0),
+            ],
+            edges: vec![
+                Member::new(0, 1),
+                Member::new(1, 2),
+                Member::new(2, 3),
+                Member::new(3, 0),
+            ],
+            connections: vec![ConnectionData::Pin(0), ConnectionData::Roller(1, 90.0)],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn empty_model_is_classified_and_solved() {
+        let model = Model::default();
+        let stability = classify(&model).unwrap();
+        assert_eq!((stability.rank, stability.modes.len()), (0, 0));
+
+        let unstable = |result: Result<_, _>| matches!(result, Err(TrussError::Unstable(_)));
+        assert!(unstable(crate::physics::solve_stiff(&model).map(|_| ())));
+        assert!(unstable(
+            crate::physics::calculate_member_stress(&model).map(|_| ())
+        ));
+        assert!(unstable(
+            crate::physics::solve_load_cases(&model).map(|_| ())
+        ));
+    }
+
+    #[test]
+    fn square_without_diagonal_is_a_mechanism() {
+        // 4 members + 3 reactions < 8 equations.
+        let stability = classify(&square()).unwrap();
+        assert_eq!(
+            stability.determinacy,
+            Determinacy::Unstable {
+                mechanisms: 1,
+                nodes: vec![2, 3]
+            }
+        );
+        assert_eq!(stability.rank, 7);
+    }
+
+    #[test]
+    fn mechanism_mode_strains_nothing() {
+        let truss = square();
+        let stability = classify(&truss).unwrap();
+        let mode = DMatrix::from_column_slice(8, 1, &stability.modes[0]);
+
+        // Aᵀ·d is the elongation of every member and the movement of every
+        // support along its reaction.
+        let elongation = equilibrium_matrix(&truss).transpose() * mode;
+        assert!(elongation.amax() < 1e-9);
+        assert_eq!(
+            stability.modes[0][4].abs().max(stability.modes[0][6].abs()),
+            1.0
+        );
+    }
+
+    #[test]
+    fn braced_square_is_determinate_then_indeterminate() {
+        let mut truss = square();
+        truss.edges.push(Member::new(0, 2));
+        assert_eq!(
+            classify(&truss).unwrap().determinacy,
+            Determinacy::Determinate
+        );
+
+        truss.edges.push(Member::new(1, 3));
+        assert_eq!(
+            classify(&truss).unwrap().determinacy,
+            Determinacy::Indeterminate { degree: 1 }
+        );
+    }
+
+    #[test]
+    fn parallel_reactions_are_unstable_even_when_counts_match() {
+        let mut truss = square();
+        truss.edges.push(Member::new(0, 2));
+        // Three vertical reactions: counts balance but nothing resists sway.
+        truss.connections = vec![
+            ConnectionData::Roller(0, 90.0),
+            ConnectionData::Roller(1, 90.0),
+            ConnectionData::Roller(3, 90.0),
+        ];
+        let stability = classify(&truss).unwrap();
+        assert_eq!(stability.members + stability.reactions, stability.equations);
+        assert!(matches!(
+            stability.determinacy,
+            Determinacy::Unstable { mechanisms: 1, .. }
+        ));
+    }
+}
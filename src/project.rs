@@ -0,0 +1,278 @@
+use std::fs;
+use std::path::Path;
+
+use crate::Model;
+use crate::TrussError;
+use crate::physics::validate;
+use crate::snap::SnapSettings;
+use crate::units::UnitSystem;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Version written to new project files. Files without a `version` field are
+/// in the legacy `test_trusses` schema and count as version 0.
+pub const PROJECT_VERSION: u64 = 2;
+
+/// Everything a `.truss` project file stores: the model, its library and
+/// the editor settings that shape what is drawn next.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Project {
+    pub version: u64,
+    #[serde(flatten)]
+    pub model: Model,
+    #[serde(default)]
+    pub editor: EditorSettings,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditorSettings {
+    /// Library indices given to newly drawn members.
+    pub active_material: usize,
+    pub active_section: usize,
+    /// Restrained direction, in degrees, given to newly placed rollers.
+    pub roller_angle: f32,
+    /// Units values are typed and shown in.
+    #[serde(default)]
+    pub units: UnitSystem,
+    #[serde(default)]
+    pub snap: SnapSettings,
+    /// Load case given to newly placed forces.
+    #[serde(default)]
+    pub active_case: usize,
+}
+
+impl Default for EditorSettings {
+    fn default() -> Self {
+        EditorSettings {
+            active_material: 0,
+            active_section: 0,
+            roller_angle: 90.0,
+            units: UnitSystem::default(),
+            snap: SnapSettings::default(),
+            active_case: 0,
+        }
+    }
+}
+
+impl Project {
+    pub fn new(model: Model, editor: EditorSettings) -> Self {
+        Project {
+            version: PROJECT_VERSION,
+            model,
+            editor,
+        }
+    }
+
+    /// Reads a project of any version, upgrading it one version at a time.
+    pub fn from_value(mut value: Value) -> Result<Project, TrussError> {
+        let mut version = match value.get("version") {
+            None => 0,
+            Some(version) => version
+                .as_u64()
+                .ok_or_else(|| TrussError::Format(format!("bad version {version}")))?,
+        };
+        if version > PROJECT_VERSION {
+            return Err(TrussError::Format(format!(
+                "version {version} is newer than this program ({PROJECT_VERSION})"
+            )));
+        }
+        while version < PROJECT_VERSION {
+            value = upgrade(version, value)?;
+            version += 1;
+        }
+        serde_json::from_value(value).map_err(|err| TrussError::Format(err.to_string()))
+    }
+
+    /// Opens a `.truss` project, a `.trussb` binary project or a legacy
+    /// `test_trusses` file, whatever version it was written in.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Project, TrussError> {
+        let path = path.as_ref();
+        let io = |err: std::io::Error| TrussError::Io(format!("{}: {err}", path.display()));
+        let value = if is_binary(path) {
+            from_binary(&fs::read(path).map_err(io)?)?
+        } else {
+            serde_json::from_str(&fs::read_to_string(path).map_err(io)?)
+                .map_err(|err| TrussError::Format(err.to_string()))?
+        };
+        let project = Project::from_value(value)?;
+        validate(&project.model)?;
+        Ok(project)
+    }
+
+    /// Saves in the format the extension names: `.json` for the legacy
+    /// schema, `.trussb` for a binary project and a `.truss` project
+    /// otherwise.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), TrussError> {
+        let path = path.as_ref();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            return self.model.save_json(path);
+        }
+        let bytes = if is_binary(path) {
+            to_binary(self)?
+        } else {
+            serde_json::to_vec_pretty(self).expect("projects serialize to JSON")
+        };
+        fs::write(path, bytes).map_err(|err| TrussError::Io(format!("{}: {err}", path.display())))
+    }
+}
+
+/// Converts a version `from` project into version `from + 1`.
+fn upgrade(from: u64, mut value: Value) -> Result<Value, TrussError> {
+    match from {
+        0 => {
+            let mut project =
+                Project::new(Model::from_json_value(value)?, EditorSettings::default());
+            project.version = 1;
+            Ok(serde_json::to_value(project).expect("projects serialize to JSON"))
+        }
+        // Version 2 added load cases and combinations. Version 1 forces are
+        // all in the one unnamed case.
+        1 => {
+            value["version"] = Value::from(2);
+            Ok(value)
+        }
+        _ => Err(TrussError::Format(format!(
+            "no upgrade from version {from}"
+        ))),
+    }
+}
+
+impl Model {
+    /// Reads the model from any file `Project::load` accepts.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Model, TrussError> {
+        Project::load(path).map(|project| project.model)
+    }
+}
+
+fn is_binary(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "trussb")
+}
+
+// Binary projects keep field names so that they migrate like JSON ones.
+#[cfg(feature = "binary")]
+fn to_binary(project: &Project) -> Result<Vec<u8>, TrussError> {
+    rmp_serde::to_vec_named(project).map_err(|err| TrussError::Format(err.to_string()))
+}
+
+#[cfg(feature = "binary")]
+fn from_binary(bytes: &[u8]) -> Result<Value, TrussError> {
+    rmp_serde::from_slice(bytes).map_err(|err| TrussError::Format(err.to_string()))
+}
+
+#[cfg(not(feature = "binary"))]
+fn to_binary(_: &Project) -> Result<Vec<u8>, TrussError> {
+    Err(TrussError::Format(
+        "binary projects need the `binary` feature".to_string(),
+    ))
+}
+
+#[cfg(not(feature = "binary"))]
+fn from_binary(_: &[u8]) -> Result<Value, TrussError> {
+    Err(TrussError::Format(
+        "binary projects need the `binary` feature".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ConnectionData;
+    use crate::Material;
+    use crate::Point;
+    use crate::Settlement;
+    use crate::physics::SolverMethod;
+
+    fn edited() -> Project {
+        let mut model = Model::load_json("test_trusses/truss1.json").unwrap();
+        let timber = model.library.set_material(Material {
+            name: "timber".to_string(),
+            youngs: 11e9,
+            yield_stress: f64::INFINITY,
+        });
+        model.edges[1].material = timber;
+        model.connections[1] = ConnectionData::Roller(1, 60.0);
+        model.settlements.push(Settlement {
+            node: 1,
+            dx: 0.0,
+            dy: -0.01,
+        });
+        model.solver = SolverMethod::Penalty;
+        Project::new(
+            model,
+            EditorSettings {
+                active_material: timber,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn project_round_trips_without_loss() {
+        let project = edited();
+        let json = serde_json::to_value(&project).unwrap();
+        let again = Project::from_value(json).unwrap();
+
+        assert_eq!(again.model.points, project.model.points);
+        assert_eq!(again.model.edges[1].material, 2);
+        assert_eq!(
+            again.model.library.materials[2],
+            project.model.library.materials[2]
+        );
+        assert!(matches!(
+            again.model.connections[1],
+            ConnectionData::Roller(1, 60.0)
+        ));
+        assert_eq!(again.model.settlements[0].dy, -0.01);
+        assert_eq!(again.model.solver, SolverMethod::Penalty);
+        assert_eq!(again.editor.active_material, 2);
+    }
+
+    #[test]
+    fn legacy_files_migrate_from_version_zero() {
+        let legacy: Value =
+            serde_json::from_str(&fs::read_to_string("test_trusses/truss1.json").unwrap()).unwrap();
+        let project = Project::from_value(legacy).unwrap();
+
+        assert_eq!(project.version, PROJECT_VERSION);
+        assert_eq!(project.model.points.len(), 3);
+        assert_eq!(project.model.force[0].p2, Point::new(0.0, -2.0));
+        assert_eq!(project.editor.roller_angle, 90.0);
+    }
+
+    #[test]
+    fn version_one_forces_land_in_the_unnamed_case() {
+        let mut json = serde_json::to_value(edited()).unwrap();
+        json["version"] = Value::from(1);
+        for force in json["force"].as_array_mut().unwrap() {
+            force.as_object_mut().unwrap().remove("case");
+        }
+        json.as_object_mut().unwrap().remove("load_cases");
+        json.as_object_mut().unwrap().remove("combinations");
+        let project = Project::from_value(json).unwrap();
+
+        assert_eq!(project.version, PROJECT_VERSION);
+        assert_eq!(project.model.force[0].case, 0);
+        assert_eq!(project.model.case_count(), 1);
+    }
+
+    #[test]
+    fn newer_versions_are_refused() {
+        let mut json = serde_json::to_value(edited()).unwrap();
+        json["version"] = Value::from(PROJECT_VERSION + 1);
+        assert!(matches!(
+            Project::from_value(json),
+            Err(TrussError::Format(_))
+        ));
+    }
+
+    #[cfg(feature = "binary")]
+    #[test]
+    fn binary_round_trips() {
+        let project = edited();
+        let bytes = to_binary(&project).unwrap();
+        let again = Project::from_value(from_binary(&bytes).unwrap()).unwrap();
+
+        assert_eq!(again.model.points, project.model.points);
+        assert_eq!(again.model.library.materials[2].yield_stress, f64::INFINITY);
+    }
+}
@@ -0,0 +1,189 @@
+use crate::Model;
+use crate::Point;
+use serde::{Deserialize, Serialize};
+
+/// Which snaps are active while drawing, and whether the grid is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SnapSettings {
+    pub show_grid: bool,
+    pub grid: bool,
+    pub nodes: bool,
+    pub midpoints: bool,
+    /// Line up horizontally or vertically with existing nodes.
+    pub align: bool,
+}
+
+impl Default for SnapSettings {
+    fn default() -> Self {
+        SnapSettings {
+            show_grid: true,
+            grid: true,
+            nodes: true,
+            midpoints: true,
+            align: true,
+        }
+    }
+}
+
+impl SnapSettings {
+    /// The switch named `kind` on the command line.
+    pub fn toggle(&mut self, kind: &str) -> Option<&mut bool> {
+        match kind {
+            "grid" => Some(&mut self.grid),
+            "nodes" => Some(&mut self.nodes),
+            "midpoints" => Some(&mut self.midpoints),
+            "align" => Some(&mut self.align),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Target {
+    Node(usize),
+    /// Midpoint of member `usize`. A node placed there splits the member.
+    Midpoint(usize),
+    Grid,
+    Free,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Snapped {
+    pub point: Point,
+    pub target: Target,
+    /// Node the point lines up with vertically (same x).
+    pub vertical: Option<usize>,
+    /// Node the point lines up with horizontally (same y).
+    pub horizontal: Option<usize>,
+}
+
+/// Snaps `cursor` to the first match among an existing node, a member
+/// midpoint, and the grid with each axis lined up with other nodes where
+/// one is within `tolerance` model units.
+pub fn snap(model: &Model, settings: &SnapSettings, cursor: Point, tolerance: f32) -> Snapped {
+    let mut snapped = Snapped {
+        point: cursor,
+        target: Target::Free,
+        vertical: None,
+        horizontal: None,
+    };
+    let nearest = |candidates: &mut dyn Iterator<Item = (usize, f32)>| {
+        candidates
+            .filter(|(_, distance)| *distance <= tolerance)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(idx, _)| idx)
+    };
+
+    if settings.nodes
+        && let Some(node) = nearest(
+            &mut (model.points.iter())
+                .map(|p| p.distance(cursor))
+                .enumerate(),
+        )
+    {
+        snapped.point = model.points[node];
+        snapped.target = Target::Node(node);
+        return snapped;
+    }
+    let midpoint = |idx: usize| {
+        let member = &model.edges[idx];
+        let (a, b) = (model.points[member.p1], model.points[member.p2]);
+        Point::new((a.x + b.x) / 2.0, (a.y + b.y) / 2.0)
+    };
+    if settings.midpoints
+        && let Some(member) =
+            nearest(&mut (0..model.edges.len()).map(|idx| (idx, midpoint(idx).distance(cursor))))
+    {
+        snapped.point = midpoint(member);
+        snapped.target = Target::Midpoint(member);
+        return snapped;
+    }
+
+    let workspace = &model.workspace;
+    if settings.grid && workspace.grid_x > 0.0 && workspace.grid_y > 0.0 {
+        snapped.point = Point::new(
+            (cursor.x / workspace.grid_x).round() * workspace.grid_x,
+            (cursor.y / workspace.grid_y).round() * workspace.grid_y,
+        );
+        snapped.target = Target::Grid;
+    }
+    if settings.align {
+        snapped.vertical = nearest(
+            &mut (model.points.iter())
+                .map(|p| (p.x - cursor.x).abs())
+                .enumerate(),
+        );
+        snapped.horizontal = nearest(
+            &mut (model.points.iter())
+                .map(|p| (p.y - cursor.y).abs())
+                .enumerate(),
+        );
+        if let Some(node) = snapped.vertical {
+            snapped.point.x = model.points[node].x;
+        }
+        if let Some(node) = snapped.horizontal {
+            snapped.point.y = model.points[node].y;
+        }
+    }
+    snapped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Member;
+    use crate::Workspace;
+
+    fn chord() -> Model {
+        Model {
+            points: vec![Point::new(0.0, 0.0), Point::new(4.0, 0.0)],
+            edges: vec![Member::new(0, 1)],
+            workspace: Workspace {
+                grid_x: 0.5,
+                grid_y: 0.5,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn nodes_win_over_midpoints_and_the_grid() {
+        let model = chord();
+        let settings = SnapSettings::default();
+
+        let on_node = snap(&model, &settings, Point::new(3.9, 0.05), 0.2);
+        assert_eq!(on_node.target, Target::Node(1));
+        assert_eq!(on_node.point, Point::new(4.0, 0.0));
+
+        let on_middle = snap(&model, &settings, Point::new(2.1, -0.1), 0.2);
+        assert_eq!(on_middle.target, Target::Midpoint(0));
+        assert_eq!(on_middle.point, Point::new(2.0, 0.0));
+    }
+
+    #[test]
+    fn grid_rounds_and_alignment_overrides_it_per_axis() {
+        let mut model = chord();
+        model.points.push(Point::new(1.3, 3.0));
+        let settings = SnapSettings::default();
+
+        let free = snap(&model, &settings, Point::new(2.8, 2.2), 0.2);
+        assert_eq!(free.target, Target::Grid);
+        assert_eq!(free.point, Point::new(3.0, 2.0));
+        assert_eq!((free.vertical, free.horizontal), (None, None));
+
+        // Close to x = 1.3, so it stays above that node instead of on the grid.
+        let above = snap(&model, &settings, Point::new(1.35, 2.2), 0.2);
+        assert_eq!(above.point, Point::new(1.3, 2.0));
+        assert_eq!(above.vertical, Some(2));
+
+        let off = SnapSettings {
+            grid: false,
+            align: false,
+            ..settings
+        };
+        let free = snap(&model, &off, Point::new(1.35, 2.2), 0.2);
+        assert_eq!(free.target, Target::Free);
+        assert_eq!(free.point, Point::new(1.35, 2.2));
+    }
+}
@@ -0,0 +1,284 @@
+use std::fmt::Write as _;
+
+use crate::physics::{AnalysisResult, LoadCaseResults};
+use crate::units::UnitSystem;
+use crate::{Entity, Model};
+
+/// How a column's values are shown.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    Index,
+    Value,
+    /// `T` for positive values, `C` for negative and `-` for zero.
+    Sign,
+    /// An index into the table's `names`.
+    Name,
+}
+
+/// Which of the results tables is shown.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Sheet {
+    #[default]
+    Members,
+    Nodes,
+    /// Extreme member forces over the load cases or combinations.
+    Envelope,
+}
+
+/// Column a table is sorted by.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Sort {
+    pub column: usize,
+    pub descending: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Row {
+    /// What clicking the row selects on the canvas.
+    pub entity: Entity,
+    /// One per column, in display units. Values that do not apply, such as
+    /// the reactions of a free node, are NaN.
+    pub values: Vec<f64>,
+}
+
+/// Results laid out as rows, in display units.
+#[derive(Debug, Clone)]
+pub struct Table {
+    pub columns: Vec<(String, Kind)>,
+    pub rows: Vec<Row>,
+    /// What `Kind::Name` columns show.
+    pub names: Vec<String>,
+}
+
+impl Table {
+    /// One row per member. Utilization is stress over the yield stress of
+    /// the member's material, and blank for materials that do not yield.
+    pub fn members(model: &Model, result: &AnalysisResult, units: UnitSystem) -> Self {
+        let columns = vec![
+            ("member".to_string(), Kind::Index),
+            ("p1".to_string(), Kind::Index),
+            ("p2".to_string(), Kind::Index),
+            (format!("length [{}]", units.length), Kind::Value),
+            (format!("force [{}]", units.force), Kind::Value),
+            (format!("stress [{}]", units.stress), Kind::Value),
+            ("utilization".to_string(), Kind::Value),
+            ("T/C".to_string(), Kind::Sign),
+        ];
+        let rows = (result.members.iter())
+            .map(|m| {
+                let yield_stress = (model.edges.get(m.member))
+                    .and_then(|edge| model.library.materials.get(edge.material))
+                    .map_or(f64::INFINITY, |material| material.yield_stress);
+                let utilization = if yield_stress.is_finite() {
+                    m.stress.abs() / yield_stress
+                } else {
+                    f64::NAN
+                };
+                Row {
+                    entity: Entity::Member(m.member),
+                    values: vec![
+                        m.member as f64,
+                        m.p1 as f64,
+                        m.p2 as f64,
+                        units.length.from_si(m.length),
+                        units.force.from_si(m.force),
+                        units.stress.from_si(m.stress),
+                        utilization,
+                        m.force,
+                    ],
+                }
+            })
+            .collect();
+        Table {
+            columns,
+            rows,
+            names: Vec::new(),
+        }
+    }
+
+    /// One row per node with its position, displacement and reaction.
+    pub fn nodes(model: &Model, result: &AnalysisResult, units: UnitSystem) -> Self {
+        let (length, force) = (units.length, units.force);
+        let columns = vec![
+            ("node".to_string(), Kind::Index),
+            (format!("x [{length}]"), Kind::Value),
+            (format!("y [{length}]"), Kind::Value),
+            (format!("dx [{length}]"), Kind::Value),
+            (format!("dy [{length}]"), Kind::Value),
+            (format!("rx [{force}]"), Kind::Value),
+            (format!("ry [{force}]"), Kind::Value),
+        ];
+        let rows = (result.displacements.iter())
+            .map(|d| {
+                let point = model.points[d.node];
+                let (rx, ry) = result
+                    .reaction(d.node)
+                    .map_or((f64::NAN, f64::NAN), |r| (r.rx, r.ry));
+                Row {
+                    entity: Entity::Node(d.node),
+                    values: vec![
+                        d.node as f64,
+                        length.from_si(point.x.into()),
+                        length.from_si(point.y.into()),
+                        length.from_si(d.dx),
+                        length.from_si(d.dy),
+                        force.from_si(rx),
+                        force.from_si(ry),
+                    ],
+                }
+            })
+            .collect();
+        Table {
+            columns,
+            rows,
+            names: Vec::new(),
+        }
+    }
+
+    /// One row per member with its largest tension and compression and the
+    /// case or combination each comes from.
+    pub fn envelope(results: &LoadCaseResults, units: UnitSystem) -> Self {
+        let force = units.force;
+        let columns = vec![
+            ("member".to_string(), Kind::Index),
+            (format!("max [{force}]"), Kind::Value),
+            ("from".to_string(), Kind::Name),
+            (format!("min [{force}]"), Kind::Value),
+            ("from".to_string(), Kind::Name),
+        ];
+        let mut names: Vec<String> = Vec::new();
+        let mut name = |by: &str| match names.iter().position(|n| n == by) {
+            Some(idx) => idx as f64,
+            None => {
+                names.push(by.to_string());
+                (names.len() - 1) as f64
+            }
+        };
+        let rows = (results.envelope.iter())
+            .map(|e| Row {
+                entity: Entity::Member(e.member),
+                values: vec![
+                    e.member as f64,
+                    force.from_si(e.max_force),
+                    name(&e.max_by),
+                    force.from_si(e.min_force),
+                    name(&e.min_by),
+                ],
+            })
+            .collect();
+        Table {
+            columns,
+            rows,
+            names,
+        }
+    }
+
+    /// Sorts the rows by one column. Blank values go last.
+    pub fn sort(&mut self, sort: Sort) {
+        if sort.column >= self.columns.len() {
+            return;
+        }
+        self.rows.sort_by(|a, b| {
+            let (a, b) = (a.values[sort.column], b.values[sort.column]);
+            match (a.is_nan(), b.is_nan()) {
+                (false, false) if sort.descending => b.total_cmp(&a),
+                (false, false) => a.total_cmp(&b),
+                (a, b) => a.cmp(&b),
+            }
+        });
+    }
+
+    /// The text of the cell at `row`, `column`, as shown in the panel.
+    pub fn cell(&self, row: usize, column: usize) -> String {
+        let value = self.rows[row].values[column];
+        match self.columns[column].1 {
+            _ if value.is_nan() => String::new(),
+            Kind::Index => format!("{}", value as usize),
+            Kind::Name => self.names[value as usize].clone(),
+            Kind::Sign if value > 0.0 => "T".to_string(),
+            Kind::Sign if value < 0.0 => "C".to_string(),
+            Kind::Sign => "-".to_string(),
+            Kind::Value if value == 0.0 => "0".to_string(),
+            Kind::Value if (1e-3..1e6).contains(&value.abs()) => format!("{value:.4}"),
+            Kind::Value => format!("{value:.3e}"),
+        }
+    }
+
+    /// Tab separated, with a header line, for pasting into a spreadsheet.
+    /// Values are written in full rather than as shown.
+    pub fn to_tsv(&self) -> String {
+        let mut out = String::new();
+        let headers: Vec<&str> = self.columns.iter().map(|(h, _)| h.as_str()).collect();
+        writeln!(out, "{}", headers.join("\t")).unwrap();
+        for (idx, row) in self.rows.iter().enumerate() {
+            let fields: Vec<String> = (self.columns.iter().enumerate())
+                .map(|(column, (_, kind))| match kind {
+                    Kind::Value if !row.values[column].is_nan() => row.values[column].to_string(),
+                    _ => self.cell(idx, column),
+                })
+                .collect();
+            writeln!(out, "{}", fields.join("\t")).unwrap();
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physics;
+
+    fn solved() -> (Model, AnalysisResult) {
+        let model = Model::from_json(include_str!("../test_trusses/truss1.json")).unwrap();
+        let result = physics::solve_stiff(&model).unwrap();
+        (model, result)
+    }
+
+    #[test]
+    fn member_rows_sort_by_any_column() {
+        let model = Model::from_json(include_str!("../test_trusses/bontruss2.json")).unwrap();
+        let result = physics::solve_stiff(&model).unwrap();
+        let mut table = Table::members(&model, &result, UnitSystem::si());
+        assert_eq!(table.rows.len(), model.edges.len());
+
+        let force = 4;
+        table.sort(Sort {
+            column: force,
+            descending: true,
+        });
+        // Members 0 and 1 carry nothing, up to rounding.
+        let loaded: Vec<usize> = (0..table.rows.len())
+            .filter(|&row| table.rows[row].values[force].abs() > 1e-9)
+            .collect();
+        let order: Vec<String> = loaded.iter().map(|&row| table.cell(row, 0)).collect();
+        assert_eq!(order, ["2", "3", "6", "7", "5", "8", "4"]);
+        let signs: Vec<String> = loaded.iter().map(|&row| table.cell(row, 7)).collect();
+        assert_eq!(signs, ["T", "T", "T", "T", "C", "C", "C"]);
+    }
+
+    #[test]
+    fn free_nodes_have_blank_reactions() {
+        let (model, result) = solved();
+        let mut table = Table::nodes(&model, &result, UnitSystem::si());
+        table.sort(Sort {
+            column: 5,
+            descending: false,
+        });
+        let supported = result.reactions.len();
+        assert!(
+            table.rows[..supported]
+                .iter()
+                .all(|r| !r.values[5].is_nan())
+        );
+        assert!(table.rows[supported..].iter().all(|r| r.values[5].is_nan()));
+
+        let tsv = table.to_tsv();
+        let lines: Vec<&str> = tsv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "node\tx [m]\ty [m]\tdx [m]\tdy [m]\trx [kN]\try [kN]"
+        );
+        assert_eq!(lines.len(), 1 + model.points.len());
+        assert!(lines.last().unwrap().ends_with("\t\t"));
+    }
+}
@@ -0,0 +1,353 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::physics::physics::unit_direction;
+
+/// The model is always stored in SI base units (m, N, Pa); these are the
+/// units values are typed and shown in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Unit {
+    M,
+    Mm,
+    Cm,
+    Ft,
+    In,
+    N,
+    KN,
+    Lbf,
+    Kip,
+    Pa,
+    KPa,
+    MPa,
+    GPa,
+    Psi,
+    Ksi,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quantity {
+    Length,
+    Force,
+    /// Stress and elastic modulus.
+    Stress,
+}
+
+const ALL: [Unit; 15] = [
+    Unit::M,
+    Unit::Mm,
+    Unit::Cm,
+    Unit::Ft,
+    Unit::In,
+    Unit::N,
+    Unit::KN,
+    Unit::Lbf,
+    Unit::Kip,
+    Unit::Pa,
+    Unit::KPa,
+    Unit::MPa,
+    Unit::GPa,
+    Unit::Psi,
+    Unit::Ksi,
+];
+
+const FOOT: f64 = 0.3048;
+const INCH: f64 = 0.0254;
+const POUND_FORCE: f64 = 4.448_221_615_260_5;
+
+impl Unit {
+    pub fn quantity(self) -> Quantity {
+        match self {
+            Unit::M | Unit::Mm | Unit::Cm | Unit::Ft | Unit::In => Quantity::Length,
+            Unit::N | Unit::KN | Unit::Lbf | Unit::Kip => Quantity::Force,
+            _ => Quantity::Stress,
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Unit::M => "m",
+            Unit::Mm => "mm",
+            Unit::Cm => "cm",
+            Unit::Ft => "ft",
+            Unit::In => "in",
+            Unit::N => "N",
+            Unit::KN => "kN",
+            Unit::Lbf => "lbf",
+            Unit::Kip => "kip",
+            Unit::Pa => "Pa",
+            Unit::KPa => "kPa",
+            Unit::MPa => "MPa",
+            Unit::GPa => "GPa",
+            Unit::Psi => "psi",
+            Unit::Ksi => "ksi",
+        }
+    }
+
+    /// Value of one of this unit in SI base units.
+    pub fn factor(self) -> f64 {
+        match self {
+            Unit::M => 1.0,
+            Unit::Mm => 1e-3,
+            Unit::Cm => 1e-2,
+            Unit::Ft => FOOT,
+            Unit::In => INCH,
+            Unit::N => 1.0,
+            Unit::KN => 1e3,
+            Unit::Lbf => POUND_FORCE,
+            Unit::Kip => 1e3 * POUND_FORCE,
+            Unit::Pa => 1.0,
+            Unit::KPa => 1e3,
+            Unit::MPa => 1e6,
+            Unit::GPa => 1e9,
+            Unit::Psi => POUND_FORCE / (INCH * INCH),
+            Unit::Ksi => 1e3 * POUND_FORCE / (INCH * INCH),
+        }
+    }
+
+    pub fn from_symbol(symbol: &str) -> Option<Unit> {
+        ALL.into_iter().find(|unit| unit.symbol() == symbol)
+    }
+
+    pub fn to_si(self, value: f64) -> f64 {
+        value * self.factor()
+    }
+
+    pub fn from_si(self, value: f64) -> f64 {
+        value / self.factor()
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+/// Units used for each kind of value shown or entered. Areas use the square
+/// of the length unit.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct UnitSystem {
+    pub length: Unit,
+    pub force: Unit,
+    pub stress: Unit,
+    pub modulus: Unit,
+}
+
+impl Default for UnitSystem {
+    fn default() -> Self {
+        UnitSystem::si()
+    }
+}
+
+impl UnitSystem {
+    pub fn si() -> Self {
+        UnitSystem {
+            length: Unit::M,
+            force: Unit::KN,
+            stress: Unit::MPa,
+            modulus: Unit::GPa,
+        }
+    }
+
+    pub fn us() -> Self {
+        UnitSystem {
+            length: Unit::Ft,
+            force: Unit::Kip,
+            stress: Unit::Ksi,
+            modulus: Unit::Ksi,
+        }
+    }
+
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "si" => Some(UnitSystem::si()),
+            "us" => Some(UnitSystem::us()),
+            _ => None,
+        }
+    }
+
+    /// Sets the unit for `kind` ("length", "force", "stress" or "modulus"),
+    /// which must measure the matching quantity.
+    pub fn set(&mut self, kind: &str, unit: Unit) -> Result<(), String> {
+        let (slot, quantity) = match kind {
+            "length" => (&mut self.length, Quantity::Length),
+            "force" => (&mut self.force, Quantity::Force),
+            "stress" => (&mut self.stress, Quantity::Stress),
+            "modulus" => (&mut self.modulus, Quantity::Stress),
+            _ => return Err(format!("no {kind} units")),
+        };
+        if unit.quantity() != quantity {
+            return Err(format!("{unit} is not a {kind} unit"));
+        }
+        *slot = unit;
+        Ok(())
+    }
+
+    pub fn area_to_si(&self, value: f64) -> f64 {
+        value * self.length.factor().powi(2)
+    }
+
+    pub fn area_from_si(&self, value: f64) -> f64 {
+        value / self.length.factor().powi(2)
+    }
+
+    /// Parses an area in m². A trailing length symbol, optionally followed
+    /// by `²` or `^2`, overrides the length unit.
+    pub fn parse_area(&self, text: &str) -> Result<f64, String> {
+        let text = text.trim();
+        let text = (text.strip_suffix('²'))
+            .or_else(|| text.strip_suffix("^2"))
+            .unwrap_or(text);
+        let (value, unit) = parse_with_unit(text, self.length)?;
+        Ok(value * unit.factor().powi(2))
+    }
+
+    pub fn area_symbol(&self) -> String {
+        format!("{}²", self.length)
+    }
+}
+
+/// Parses `text` as a value in SI base units. A trailing unit symbol,
+/// with or without a space, overrides `default`.
+pub fn parse_value(text: &str, default: Unit) -> Result<f64, String> {
+    let (value, unit) = parse_with_unit(text, default)?;
+    Ok(unit.to_si(value))
+}
+
+/// Splits `text` into its number and the unit it is in.
+fn parse_with_unit(text: &str, default: Unit) -> Result<(f64, Unit), String> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| c.is_ascii_alphabetic() && c != 'e' && c != 'E')
+        .unwrap_or(text.len());
+    let (number, symbol) = text.split_at(split);
+    let value: f64 = number
+        .trim()
+        .parse()
+        .map_err(|_| format!("not a number: {text}"))?;
+    let unit = match symbol {
+        "" => default,
+        _ => Unit::from_symbol(symbol).ok_or_else(|| format!("unknown unit {symbol}"))?,
+    };
+    if unit.quantity() != default.quantity() {
+        return Err(format!("expected {}, not {unit}", default));
+    }
+    Ok((value, unit))
+}
+
+/// A force as typed, in N.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ForceEntry {
+    /// `fx,fy`, or `mag@angle` with the angle in degrees counterclockwise
+    /// from +x. Positive y is up.
+    Components(f64, f64),
+    /// A bare magnitude, pointing from the node toward the mouse, or away
+    /// from it if negative.
+    Toward(f64),
+}
+
+/// Parses a force as `fx,fy`, `mag@angle` or a bare magnitude. Each value
+/// takes an optional unit like `parse_value`.
+pub fn parse_force(text: &str, default: Unit) -> Result<ForceEntry, String> {
+    if let Some((fx, fy)) = text.split_once(',') {
+        return Ok(ForceEntry::Components(
+            parse_value(fx, default)?,
+            parse_value(fy, default)?,
+        ));
+    }
+    if let Some((mag, angle)) = text.split_once('@') {
+        let mag = parse_value(mag, default)?;
+        let angle: f32 = (angle.trim().trim_end_matches('°').parse())
+            .map_err(|_| format!("not an angle: {angle}"))?;
+        let (c, s) = unit_direction::<f64>(angle);
+        return Ok(ForceEntry::Components(mag * c, mag * s));
+    }
+    parse_value(text, default).map(ForceEntry::Toward)
+}
+
+impl fmt::Display for UnitSystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "length {}, force {}, stress {}, modulus {}",
+            self.length, self.force, self.stress, self.modulus
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() <= 1e-12 * a.abs().max(b.abs())
+    }
+
+    #[test]
+    fn conversions_match_reference_values() {
+        assert!(close(Unit::Ft.to_si(1.0), 0.3048));
+        assert!(close(Unit::Kip.to_si(1.0), 4448.2216152605));
+        assert!(close(Unit::Ksi.to_si(1.0), 6.894_757_293_168e6));
+        assert!(close(Unit::GPa.from_si(69e9), 69.0));
+        // 29000 ksi is the usual modulus of steel, about 200 GPa.
+        assert!((Unit::GPa.from_si(Unit::Ksi.to_si(29000.0)) - 199.95).abs() < 0.01);
+        assert!(close(UnitSystem::us().area_to_si(1.0), 0.3048 * 0.3048));
+
+        for unit in ALL {
+            assert_eq!(Unit::from_symbol(unit.symbol()), Some(unit));
+            assert!(close(unit.from_si(unit.to_si(3.7)), 3.7));
+        }
+    }
+
+    #[test]
+    fn parsing_accepts_an_optional_unit() {
+        assert_eq!(parse_value("12", Unit::KN), Ok(12e3));
+        assert_eq!(parse_value("12 N", Unit::KN), Ok(12.0));
+        assert_eq!(parse_value("1.5e3kN", Unit::KN), Ok(1.5e6));
+        assert!(close(
+            parse_value("2kip", Unit::KN).unwrap(),
+            8896.443230521
+        ));
+        assert!(parse_value("2 MPa", Unit::KN).is_err());
+        assert!(parse_value("two", Unit::KN).is_err());
+    }
+
+    #[test]
+    fn areas_take_squared_length_units() {
+        let us = UnitSystem::us();
+        assert!(close(us.parse_area("2").unwrap(), 2.0 * 0.3048 * 0.3048));
+        assert!(close(us.parse_area("300 mm²").unwrap(), 300e-6));
+        assert!(close(us.parse_area("1cm^2").unwrap(), 1e-4));
+        assert!(us.parse_area("2 kN").is_err());
+    }
+
+    #[test]
+    fn forces_parse_as_components_angles_or_magnitudes() {
+        assert_eq!(
+            parse_force("3, -4 N", Unit::KN),
+            Ok(ForceEntry::Components(3e3, -4.0))
+        );
+        assert_eq!(
+            parse_force("2@270", Unit::KN),
+            Ok(ForceEntry::Components(0.0, -2e3))
+        );
+        let Ok(ForceEntry::Components(fx, fy)) = parse_force("10kN@30", Unit::N) else {
+            panic!("not components");
+        };
+        assert!(close(fx, 10e3 * 3f64.sqrt() / 2.0) && close(fy, 5e3));
+        assert_eq!(parse_force("-5", Unit::KN), Ok(ForceEntry::Toward(-5e3)));
+        assert!(parse_force("5@up", Unit::KN).is_err());
+        assert!(parse_force("1,2,3", Unit::KN).is_err());
+    }
+
+    #[test]
+    fn units_only_go_to_matching_quantities() {
+        let mut units = UnitSystem::si();
+        assert!(units.set("force", Unit::Lbf).is_ok());
+        assert!(units.set("modulus", Unit::Ksi).is_ok());
+        assert!(units.set("length", Unit::Kip).is_err());
+        assert_eq!(units.force, Unit::Lbf);
+        assert_eq!(units.length, Unit::M);
+    }
+}
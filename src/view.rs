@@ -0,0 +1,162 @@
+use crate::Point;
+use crate::Workspace;
+use egui::{Pos2, Rect, Vec2};
+
+/// Pixels left free around the structure by `View::fit`.
+const FIT_MARGIN: f32 = 40.0;
+/// Zoom limits, in pixels per model unit.
+const MIN_SCALE: f32 = 1e-3;
+const MAX_SCALE: f32 = 1e6;
+
+/// Maps world coordinates (model units, y up) to screen pixels (y down).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct View {
+    /// Screen position of the world origin.
+    pub origin: Pos2,
+    /// Pixels per model unit.
+    pub scale: f32,
+}
+
+impl Default for View {
+    fn default() -> Self {
+        View {
+            origin: Pos2::ZERO,
+            scale: 1.0,
+        }
+    }
+}
+
+impl View {
+    /// Places the workspace at the top left of `rect` with its axes where
+    /// the workspace block puts them.
+    pub fn from_workspace(workspace: &Workspace, rect: Rect) -> Self {
+        let scale = workspace.width_pixels / workspace.width;
+        View {
+            origin: rect.left_top()
+                + Vec2::new(
+                    workspace.yaxis_from_left,
+                    workspace.height - workspace.xaxis_from_bottom,
+                ) * scale,
+            scale,
+        }
+    }
+
+    pub fn to_screen(&self, point: Point) -> Pos2 {
+        self.origin + self.vector_to_screen(point.x, point.y)
+    }
+
+    pub fn to_world(&self, pos: Pos2) -> Point {
+        let offset = (pos - self.origin) / self.scale;
+        Point::new(offset.x, -offset.y)
+    }
+
+    /// Scales the view by `factor` while keeping the world point under
+    /// `anchor` where it is on screen.
+    pub fn zoom_about(&mut self, anchor: Pos2, factor: f32) {
+        let scale = (self.scale * factor).clamp(MIN_SCALE, MAX_SCALE);
+        self.origin = anchor + (self.origin - anchor) * (scale / self.scale);
+        self.scale = scale;
+    }
+
+    pub fn pan(&mut self, delta: Vec2) {
+        self.origin += delta;
+    }
+
+    /// The view that shows all of `points` centred in `rect` with a margin
+    /// of `FIT_MARGIN` on every side, or `None` if there are none.
+    pub fn fit(&self, points: &[Point], rect: Rect) -> Option<Self> {
+        let first = points.first()?;
+        let (mut min, mut max) = (*first, *first);
+        for p in points {
+            min = Point::new(min.x.min(p.x), min.y.min(p.y));
+            max = Point::new(max.x.max(p.x), max.y.max(p.y));
+        }
+        let room = (rect.size() - Vec2::splat(2.0 * FIT_MARGIN)).max(Vec2::splat(1.0));
+        let scale = [room.x / (max.x - min.x), room.y / (max.y - min.y)]
+            .into_iter()
+            .filter(|s| s.is_finite())
+            .fold(f32::INFINITY, f32::min);
+        // A single node, or nodes on one spot, keep the current zoom.
+        let scale = if scale.is_finite() {
+            scale.clamp(MIN_SCALE, MAX_SCALE)
+        } else {
+            self.scale
+        };
+        let centre = Point::new((min.x + max.x) / 2.0, (min.y + max.y) / 2.0);
+        let mut view = View {
+            origin: Pos2::ZERO,
+            scale,
+        };
+        view.origin = rect.center() - view.vector_to_screen(centre.x, centre.y);
+        Some(view)
+    }
+
+    /// A world displacement or direction as a screen offset.
+    pub fn vector_to_screen(&self, dx: f32, dy: f32) -> Vec2 {
+        Vec2::new(dx, -dy) * self.scale
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn workspace_axes_land_where_the_block_says() {
+        let workspace = Workspace::default();
+        let rect = Rect::from_min_size(Pos2::new(10.0, 20.0), Vec2::new(926.0, 600.0));
+        let view = View::from_workspace(&workspace, rect);
+        let scale = 926.0 / 30.0;
+
+        // The origin is 15 units from the left and 6 up from the bottom of
+        // the 18 unit high workspace.
+        let origin = view.to_screen(Point::new(0.0, 0.0));
+        assert!((origin.x - (10.0 + 15.0 * scale)).abs() < 1e-3);
+        assert!((origin.y - (20.0 + 12.0 * scale)).abs() < 1e-3);
+
+        // y points up in the world and down on screen.
+        let above = view.to_screen(Point::new(0.0, 1.0));
+        assert!((origin.y - above.y - scale).abs() < 1e-3);
+
+        let point = Point::new(3.5, -2.25);
+        let back = view.to_world(view.to_screen(point));
+        assert!(back.distance(point) < 1e-5);
+    }
+
+    #[test]
+    fn zoom_keeps_the_point_under_the_cursor() {
+        let mut view = View::default();
+        let cursor = Pos2::new(120.0, 80.0);
+        let under = view.to_world(cursor);
+        view.zoom_about(cursor, 2.5);
+        assert_eq!(view.scale, 2.5);
+        assert!(view.to_screen(under).distance(cursor) < 1e-3);
+
+        view.pan(Vec2::new(5.0, -3.0));
+        assert!(
+            view.to_screen(under)
+                .distance(cursor + Vec2::new(5.0, -3.0))
+                < 1e-3
+        );
+    }
+
+    #[test]
+    fn fit_shows_every_node() {
+        let rect = Rect::from_min_size(Pos2::ZERO, Vec2::new(800.0, 600.0));
+        let points = [
+            Point::new(0.0, 0.0),
+            Point::new(16.0, 0.0),
+            Point::new(8.0, 12.0),
+        ];
+        let view = View::default().fit(&points, rect).unwrap();
+        for p in points {
+            assert!(rect.shrink(FIT_MARGIN - 1e-3).contains(view.to_screen(p)));
+        }
+        // The taller side fills the height.
+        assert!((view.scale - (600.0 - 2.0 * FIT_MARGIN) / 12.0).abs() < 1e-4);
+
+        let single = View::default().fit(&points[..1], rect).unwrap();
+        assert_eq!(single.to_screen(points[0]), rect.center());
+        assert!(View::default().fit(&[], rect).is_none());
+    }
+}